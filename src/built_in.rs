@@ -1,12 +1,111 @@
-use crate::{error::RuntimeError, list, value::Value};
+use crate::{
+    error::{Arity, RuntimeError, RuntimeErrorKind},
+    eval::Runtime,
+    list,
+    number::{self, Number},
+    parse,
+    value::{Atom, Function, Pair, Value},
+};
+use num_rational::BigRational;
+use num_traits::Signed;
 use std::rc::Rc;
 
 mod name {
     pub const ADD: &str = "+";
     pub const SUB: &str = "-";
     pub const MUL: &str = "*";
+    pub const DIV: &str = "/";
     pub const EQ: &str = "=";
+    pub const LT: &str = "<";
+    pub const GT: &str = ">";
+    pub const LE: &str = "<=";
+    pub const GE: &str = ">=";
+    pub const QUOTIENT: &str = "quotient";
+    pub const REMAINDER: &str = "remainder";
+    pub const MODULO: &str = "modulo";
+    pub const ABS: &str = "abs";
+    pub const MIN: &str = "min";
+    pub const MAX: &str = "max";
+    pub const SQRT: &str = "sqrt";
+    pub const EXPT: &str = "expt";
+    pub const EXP: &str = "exp";
+    pub const LOG: &str = "log";
+    pub const SIN: &str = "sin";
+    pub const COS: &str = "cos";
+    pub const TAN: &str = "tan";
+    pub const ASIN: &str = "asin";
+    pub const ACOS: &str = "acos";
+    pub const ATAN: &str = "atan";
+    pub const FLOOR: &str = "floor";
+    pub const CEILING: &str = "ceiling";
+    pub const ROUND: &str = "round";
+    pub const TRUNCATE: &str = "truncate";
+    pub const EXACT_TO_INEXACT: &str = "exact->inexact";
+    pub const NUMERATOR: &str = "numerator";
+    pub const DENOMINATOR: &str = "denominator";
     pub const PRINTLN: &str = "println";
+    pub const SET_CAR: &str = "set-car!";
+    pub const SET_CDR: &str = "set-cdr!";
+    pub const CAR: &str = "car";
+    pub const CDR: &str = "cdr";
+    pub const CONS: &str = "cons";
+    pub const LIST: &str = "list";
+    pub const LENGTH: &str = "length";
+    pub const APPEND: &str = "append";
+    pub const REVERSE: &str = "reverse";
+    pub const LIST_REF: &str = "list-ref";
+    pub const LIST_TAIL: &str = "list-tail";
+    pub const MAP: &str = "map";
+    pub const FOR_EACH: &str = "for-each";
+    pub const FILTER: &str = "filter";
+    pub const REDUCE: &str = "reduce";
+    pub const FOLD_LEFT: &str = "fold-left";
+    pub const FOLD_RIGHT: &str = "fold-right";
+    pub const APPLY: &str = "apply";
+    pub const ASSOC: &str = "assoc";
+    pub const MEMBER: &str = "member";
+    pub const SORT: &str = "sort";
+    pub const IS_NULL: &str = "null?";
+    pub const IS_PAIR: &str = "pair?";
+    pub const IS_LIST: &str = "list?";
+    pub const IS_SYMBOL: &str = "symbol?";
+    pub const IS_STRING: &str = "string?";
+    pub const IS_NUMBER: &str = "number?";
+    pub const IS_BOOLEAN: &str = "boolean?";
+    pub const IS_PROCEDURE: &str = "procedure?";
+    pub const IS_EQ: &str = "eq?";
+    pub const IS_EQV: &str = "eqv?";
+    pub const IS_EQUAL: &str = "equal?";
+    pub const STRING_LENGTH: &str = "string-length";
+    pub const SUBSTRING: &str = "substring";
+    pub const STRING_APPEND: &str = "string-append";
+    pub const STRING_REF: &str = "string-ref";
+    pub const STRING_SPLIT: &str = "string-split";
+    pub const STRING_JOIN: &str = "string-join";
+    pub const STRING_UPCASE: &str = "string-upcase";
+    pub const STRING_DOWNCASE: &str = "string-downcase";
+    pub const STRING_TRIM: &str = "string-trim";
+    pub const STRING_CONTAINS: &str = "string-contains";
+    pub const STRING_REPLACE: &str = "string-replace";
+    pub const STRING_TO_LIST: &str = "string->list";
+    pub const STRING_TO_SYMBOL: &str = "string->symbol";
+    pub const SYMBOL_TO_STRING: &str = "symbol->string";
+    pub const NUMBER_TO_STRING: &str = "number->string";
+    pub const STRING_TO_NUMBER: &str = "string->number";
+    pub const IS_CHAR: &str = "char?";
+    pub const CHAR_TO_INTEGER: &str = "char->integer";
+    pub const INTEGER_TO_CHAR: &str = "integer->char";
+    pub const IS_CHAR_ALPHABETIC: &str = "char-alphabetic?";
+    pub const IS_CHAR_NUMERIC: &str = "char-numeric?";
+    pub const IS_CHAR_WHITESPACE: &str = "char-whitespace?";
+    pub const CHAR_UPCASE: &str = "char-upcase";
+    pub const CHAR_DOWNCASE: &str = "char-downcase";
+    /// Compositions of `car` and `cdr`
+    pub const CXRS: &[&str] = &[
+        "caar", "cadr", "cdar", "cddr", "caaar", "caadr", "cadar", "caddr", "cdaar", "cdadr",
+        "cddar", "cdddr", "caaaar", "caaadr", "caadar", "caaddr", "cadaar", "cadadr", "caddar",
+        "cadddr", "cdaaar", "cdaadr", "cdadar", "cdaddr", "cddaar", "cddadr", "cdddar", "cddddr",
+    ];
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -14,94 +113,1077 @@ pub enum BuiltIn {
     Add,
     Sub,
     Mul,
+    Div,
     Eq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Quotient,
+    Remainder,
+    Modulo,
+    Abs,
+    Min,
+    Max,
+    Sqrt,
+    Expt,
+    Exp,
+    Log,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Floor,
+    Ceiling,
+    Round,
+    Truncate,
+    ExactToInexact,
+    Numerator,
+    Denominator,
     Println,
+    SetCar,
+    SetCdr,
+    Car,
+    Cdr,
+    /// One of the compositions of `car` and `cdr` in `name::CXRS`
+    Cxr(&'static str),
+    Cons,
+    List,
+    Length,
+    Append,
+    Reverse,
+    ListRef,
+    ListTail,
+    Map,
+    ForEach,
+    Filter,
+    Reduce,
+    FoldLeft,
+    FoldRight,
+    /// Called in tail position by the runtime, rather than through `BuiltIn::eval`
+    Apply,
+    Assoc,
+    Member,
+    Sort,
+    IsNull,
+    IsPair,
+    IsList,
+    IsSymbol,
+    IsString,
+    IsNumber,
+    IsBoolean,
+    IsProcedure,
+    IsEq,
+    IsEqv,
+    IsEqual,
+    StringLength,
+    Substring,
+    StringAppend,
+    StringRef,
+    StringSplit,
+    StringJoin,
+    StringUpcase,
+    StringDowncase,
+    StringTrim,
+    StringContains,
+    StringReplace,
+    StringToList,
+    StringToSymbol,
+    SymbolToString,
+    NumberToString,
+    StringToNumber,
+    IsChar,
+    CharToInteger,
+    IntegerToChar,
+    IsCharAlphabetic,
+    IsCharNumeric,
+    IsCharWhitespace,
+    CharUpcase,
+    CharDowncase,
 }
 
-fn check_args_length(args: &Rc<Value>, required_length: usize) -> Result<(), RuntimeError> {
-    if !list::is_list(args) {
-        return Err(RuntimeError::Message(format!(
-            "arguments is not in a list: {}",
-            args
-        )));
+fn check_args_arity(args: &Rc<Value>, arity: Arity) -> Result<Vec<Rc<Value>>, RuntimeError> {
+    let args = list::to_vec(&**args).ok_or_else(|| RuntimeErrorKind::NotAList(args.clone()))?;
+    if !arity.contains(args.len()) {
+        return Err(RuntimeErrorKind::ArityMismatch {
+            expected: arity,
+            found: args.len(),
+        }
+        .into());
     }
-    let found_length = list::length(args);
-    if found_length != required_length {
-        return Err(RuntimeError::Message(format!(
-            "expected {} arguments, found {}",
-            required_length, found_length
-        )));
+    Ok(args)
+}
+
+fn check_args_length(
+    args: &Rc<Value>,
+    required_length: usize,
+) -> Result<Vec<Rc<Value>>, RuntimeError> {
+    check_args_arity(args, Arity::exactly(required_length))
+}
+
+fn pair_arg(arg: &Rc<Value>) -> Result<&Pair, RuntimeError> {
+    if let Value::Pair(pair) = &**arg {
+        Ok(pair)
+    } else {
+        Err(RuntimeErrorKind::TypeMismatch {
+            expected: "pair",
+            found: arg.clone(),
+        }
+        .into())
     }
-    Ok(())
 }
 
-fn add(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
-    use crate::value::{Atom::*, Value::*};
-    check_args_length(args, 2)?;
-    let (lhs, rhs) = list::take2(args.clone());
-    let result = match (&*lhs, &*rhs) {
-        (Atom(I64(lhs)), Atom(I64(rhs))) => Atom(I64(lhs + rhs)),
-        _ => {
-            return Err(RuntimeError::Message(format!(
-                "incorrect types in arguments to add"
-            )))
+fn list_arg(arg: &Rc<Value>) -> Result<Vec<Rc<Value>>, RuntimeError> {
+    list::to_vec(&**arg).ok_or_else(|| RuntimeErrorKind::NotAList(arg.clone()).into())
+}
+
+/// An index into `value`. Integers too large to be an index of anything are out of range.
+fn index_arg(arg: &Rc<Value>, value: &Rc<Value>) -> Result<usize, RuntimeError> {
+    let out_of_range = || {
+        RuntimeErrorKind::IndexOutOfRange {
+            index: arg.clone(),
+            value: value.clone(),
         }
+        .into()
     };
-    Ok(Rc::new(result))
+    match &**arg {
+        Value::Atom(Atom::I64(i)) if *i >= 0 => usize::try_from(*i).map_err(|_| out_of_range()),
+        Value::Atom(Atom::BigInt(i)) if i.is_positive() => Err(out_of_range()),
+        _ => Err(RuntimeErrorKind::TypeMismatch {
+            expected: "index",
+            found: arg.clone(),
+        }
+        .into()),
+    }
+}
+
+fn string_arg(arg: &Rc<Value>) -> Result<&str, RuntimeError> {
+    if let Value::Atom(Atom::String(s)) = &**arg {
+        Ok(s)
+    } else {
+        Err(RuntimeErrorKind::TypeMismatch {
+            expected: "string",
+            found: arg.clone(),
+        }
+        .into())
+    }
+}
+
+fn char_arg(arg: &Rc<Value>) -> Result<char, RuntimeError> {
+    if let Value::Atom(Atom::Char(c)) = &**arg {
+        Ok(*c)
+    } else {
+        Err(RuntimeErrorKind::TypeMismatch {
+            expected: "char",
+            found: arg.clone(),
+        }
+        .into())
+    }
+}
+
+/// A string which is to be searched for, which can't be empty
+fn pattern_arg(arg: &Rc<Value>) -> Result<&str, RuntimeError> {
+    match string_arg(arg)? {
+        "" => Err(RuntimeErrorKind::TypeMismatch {
+            expected: "non-empty string",
+            found: arg.clone(),
+        }
+        .into()),
+        pattern => Ok(pattern),
+    }
+}
+
+fn number_arg(arg: &Rc<Value>) -> Result<Number, RuntimeError> {
+    match &**arg {
+        Value::Atom(atom) => Number::from_atom(atom),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        RuntimeErrorKind::TypeMismatch {
+            expected: "number",
+            found: arg.clone(),
+        }
+        .into()
+    })
+}
+
+fn number_args(args: &Rc<Value>, arity: Arity) -> Result<Vec<Number>, RuntimeError> {
+    check_args_arity(args, arity)?
+        .iter()
+        .map(number_arg)
+        .collect()
+}
+
+fn non_zero(divisor: Number) -> Result<Number, RuntimeError> {
+    if divisor.is_zero() {
+        Err(RuntimeErrorKind::DivisionByZero.into())
+    } else {
+        Ok(divisor)
+    }
+}
+
+/// Checks for division by exact zero. Division by inexact zero gives an infinity or NaN.
+fn non_exact_zero(divisor: Number) -> Result<Number, RuntimeError> {
+    if divisor.is_exact() {
+        non_zero(divisor)
+    } else {
+        Ok(divisor)
+    }
+}
+
+/// Combines the arguments from left to right, starting with `identity`
+fn fold(
+    args: &Rc<Value>,
+    identity: i64,
+    f: fn(&Number, &Number) -> Number,
+) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::at_least(0))?;
+    let result = args
+        .iter()
+        .fold(Number::I64(identity), |acc, arg| f(&acc, arg));
+    Ok(Rc::new(result.into_value()))
+}
+
+/// Combines the arguments from left to right, or with `identity` on the left if there is only
+/// one (so `(- x)` negates `x`)
+fn fold_inverse(
+    args: &Rc<Value>,
+    identity: i64,
+    f: fn(&Number, Number) -> Result<Number, RuntimeError>,
+) -> Result<Rc<Value>, RuntimeError> {
+    let mut args = number_args(args, Arity::at_least(1))?;
+    if args.len() == 1 {
+        args.insert(0, Number::I64(identity));
+    }
+    let mut args = args.into_iter();
+    let first = args.next().expect("at least one argument");
+    let result = args.try_fold(first, |acc, arg| f(&acc, arg))?;
+    Ok(Rc::new(result.into_value()))
+}
+
+/// Whether each pair of adjacent arguments are ordered by `f`
+fn chain(args: &Rc<Value>, f: fn(&Number, &Number) -> bool) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::at_least(1))?;
+    let result = args.windows(2).all(|pair| f(&pair[0], &pair[1]));
+    Ok(Rc::new(Value::bool(result)))
+}
+
+fn add(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    fold(args, 0, Number::add)
 }
 
 fn sub(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
-    use crate::value::{Atom::*, Value::*};
-    check_args_length(args, 2)?;
-    let (lhs, rhs) = list::take2(args.clone());
-    let result = match (&*lhs, &*rhs) {
-        (Atom(I64(lhs)), Atom(I64(rhs))) => Atom(I64(lhs - rhs)),
-        _ => {
-            return Err(RuntimeError::Message(format!(
-                "incorrect types in arguments to sub"
-            )))
+    fold_inverse(args, 0, |lhs, rhs| Ok(lhs.sub(&rhs)))
+}
+
+fn mul(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    fold(args, 1, Number::mul)
+}
+
+/// Division, which is exact if all the arguments are exact
+fn div(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    fold_inverse(args, 1, |lhs, rhs| Ok(lhs.div(&non_exact_zero(rhs)?)))
+}
+
+fn eq(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    chain(args, Number::eq)
+}
+
+fn lt(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    chain(args, Number::lt)
+}
+
+fn gt(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    chain(args, Number::gt)
+}
+
+fn le(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    chain(args, Number::le)
+}
+
+fn ge(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    chain(args, Number::ge)
+}
+
+fn integer_division(
+    args: &Rc<Value>,
+    f: fn(&Number, &Number) -> Number,
+) -> Result<Rc<Value>, RuntimeError> {
+    let mut args = number_args(args, Arity::exactly(2))?;
+    let divisor = non_zero(args.pop().expect("two arguments"))?;
+    Ok(Rc::new(f(&args[0], &divisor).into_value()))
+}
+
+fn quotient(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    integer_division(args, Number::quotient)
+}
+
+fn remainder(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    integer_division(args, Number::remainder)
+}
+
+fn modulo(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    integer_division(args, Number::modulo)
+}
+
+fn abs(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::exactly(1))?;
+    Ok(Rc::new(args[0].abs().into_value()))
+}
+
+/// The argument which `f` prefers over all the others, which is inexact if any argument is
+fn select(args: &Rc<Value>, f: fn(&Number, &Number) -> bool) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::at_least(1))?;
+    let exact = args.iter().all(Number::is_exact);
+    let mut args = args.into_iter();
+    let first = args.next().expect("at least one argument");
+    let selected = args.fold(first, |acc, arg| if f(&arg, &acc) { arg } else { acc });
+    let selected = if exact {
+        selected
+    } else {
+        selected.to_inexact()
+    };
+    Ok(Rc::new(selected.into_value()))
+}
+
+fn min(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    select(args, Number::lt)
+}
+
+fn max(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    select(args, Number::gt)
+}
+
+fn unary(args: &Rc<Value>, f: fn(&Number) -> Number) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::exactly(1))?;
+    Ok(Rc::new(f(&args[0]).into_value()))
+}
+
+/// Applies a float function, whose result is always inexact
+fn unary_f64(args: &Rc<Value>, f: fn(f64) -> f64) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::exactly(1))?;
+    Ok(Rc::new(Value::f64(f(args[0].to_f64()))))
+}
+
+fn sqrt(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, Number::sqrt)
+}
+
+fn expt(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(args, Arity::exactly(2))?;
+    let power = args[0]
+        .pow(&args[1])
+        .ok_or(RuntimeErrorKind::ExponentTooLarge)?;
+    Ok(Rc::new(power.into_value()))
+}
+
+fn exp(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary_f64(args, f64::exp)
+}
+
+/// The natural logarithm, or the logarithm in the base given by the second argument
+fn log(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(
+        args,
+        Arity {
+            min: 1,
+            max: Some(2),
+        },
+    )?;
+    let result = match args.as_slice() {
+        [x] => x.to_f64().ln(),
+        [x, base] => x.to_f64().log(base.to_f64()),
+        _ => unreachable!(),
+    };
+    Ok(Rc::new(Value::f64(result)))
+}
+
+fn sin(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary_f64(args, f64::sin)
+}
+
+fn cos(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary_f64(args, f64::cos)
+}
+
+fn tan(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary_f64(args, f64::tan)
+}
+
+fn asin(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary_f64(args, f64::asin)
+}
+
+fn acos(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary_f64(args, f64::acos)
+}
+
+/// The arctangent, or with two arguments `(atan y x)` the angle of the point `(x, y)`
+fn atan(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = number_args(
+        args,
+        Arity {
+            min: 1,
+            max: Some(2),
+        },
+    )?;
+    let result = match args.as_slice() {
+        [x] => x.to_f64().atan(),
+        [y, x] => y.to_f64().atan2(x.to_f64()),
+        _ => unreachable!(),
+    };
+    Ok(Rc::new(Value::f64(result)))
+}
+
+fn floor(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, |x| x.round_with(f64::floor, BigRational::floor))
+}
+
+fn ceiling(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, |x| x.round_with(f64::ceil, BigRational::ceil))
+}
+
+/// Rounds to the nearest integer, rounding halfway cases to even
+fn round(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, |x| {
+        x.round_with(f64::round_ties_even, number::round_ties_even)
+    })
+}
+
+fn truncate(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, |x| x.round_with(f64::trunc, BigRational::trunc))
+}
+
+fn exact_to_inexact(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, Number::to_inexact)
+}
+
+fn numerator(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, Number::numerator)
+}
+
+fn denominator(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    unary(args, Number::denominator)
+}
+
+fn println(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    println!("{}", args[0]);
+    Ok(Rc::new(Value::nil()))
+}
+
+fn set_car(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    pair_arg(&args[0])?.set_car(args[1].clone());
+    Ok(Rc::new(Value::nil()))
+}
+
+fn set_cdr(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    pair_arg(&args[0])?.set_cdr(args[1].clone());
+    Ok(Rc::new(Value::nil()))
+}
+
+fn car(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(pair_arg(&args[0])?.car())
+}
+
+fn cdr(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(pair_arg(&args[0])?.cdr())
+}
+
+/// Applies the `a`s and `d`s in the name, from right to left, as `car` and `cdr`
+fn cxr(name: &str, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    name[1..name.len() - 1]
+        .chars()
+        .rev()
+        .try_fold(args[0].clone(), |value, op| {
+            let pair = pair_arg(&value)?;
+            Ok(if op == 'a' { pair.car() } else { pair.cdr() })
+        })
+}
+
+fn cons(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    Ok(Rc::new(Value::pair(args[0].clone(), args[1].clone())))
+}
+
+fn list(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    Ok(list::from_rc_vec(check_args_arity(
+        args,
+        Arity::at_least(0),
+    )?))
+}
+
+fn length(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let length =
+        list::length(&args[0]).ok_or_else(|| RuntimeErrorKind::NotAList(args[0].clone()))?;
+    Ok(Rc::new(Value::i64(length as i64)))
+}
+
+/// Concatenates lists. The last argument is shared with the result rather than copied, and can
+/// be any value.
+fn append(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let mut args = check_args_arity(args, Arity::at_least(0))?;
+    let end = args.pop().unwrap_or_else(|| Rc::new(Value::nil()));
+    let mut values = Vec::new();
+    for arg in &args {
+        values.extend(list_arg(arg)?);
+    }
+    Ok(list::from_rc_vec_with_end(values, end))
+}
+
+fn reverse(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let mut values = list_arg(&args[0])?;
+    values.reverse();
+    Ok(list::from_rc_vec(values))
+}
+
+/// The list remaining after dropping `index` elements from the front of `list`
+fn drop_front(list: &Rc<Value>, index: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let mut tail = list.clone();
+    for _ in 0..index_arg(index, list)? {
+        tail = match &*tail {
+            Value::Pair(pair) => pair.cdr(),
+            Value::Atom(Atom::Nil) => {
+                return Err(RuntimeErrorKind::IndexOutOfRange {
+                    index: index.clone(),
+                    value: list.clone(),
+                }
+                .into())
+            }
+            _ => return Err(RuntimeErrorKind::NotAList(list.clone()).into()),
+        };
+    }
+    Ok(tail)
+}
+
+fn list_ref(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    match &*drop_front(&args[0], &args[1])? {
+        Value::Pair(pair) => Ok(pair.car()),
+        Value::Atom(Atom::Nil) => Err(RuntimeErrorKind::IndexOutOfRange {
+            index: args[1].clone(),
+            value: args[0].clone(),
+        }
+        .into()),
+        _ => Err(RuntimeErrorKind::NotAList(args[0].clone()).into()),
+    }
+}
+
+fn list_tail(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    drop_front(&args[0], &args[1])
+}
+
+fn call(
+    runtime: &mut Runtime,
+    function: &Rc<Value>,
+    args: Vec<Rc<Value>>,
+) -> Result<Rc<Value>, RuntimeError> {
+    runtime.call(function, &list::from_rc_vec(args))
+}
+
+/// The elements at each position of the lists, up to the length of the shortest list
+fn zip_lists(lists: &[Rc<Value>]) -> Result<Vec<Vec<Rc<Value>>>, RuntimeError> {
+    let lists = lists.iter().map(list_arg).collect::<Result<Vec<_>, _>>()?;
+    let length = lists.iter().map(Vec::len).min().unwrap_or(0);
+    Ok((0..length)
+        .map(|i| lists.iter().map(|list| list[i].clone()).collect())
+        .collect())
+}
+
+/// Calls the function in the first argument with the corresponding elements of each of the lists
+/// in the remaining arguments, stopping at the end of the shortest list
+fn map(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(args, Arity::at_least(2))?;
+    let results = zip_lists(&args[1..])?
+        .into_iter()
+        .map(|elements| call(runtime, &args[0], elements))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(list::from_rc_vec(results))
+}
+
+fn for_each(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(args, Arity::at_least(2))?;
+    for elements in zip_lists(&args[1..])? {
+        call(runtime, &args[0], elements)?;
+    }
+    Ok(Rc::new(Value::nil()))
+}
+
+fn filter(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    let mut results = Vec::new();
+    for element in list_arg(&args[1])? {
+        if call(runtime, &args[0], vec![element.clone()])?.is_true() {
+            results.push(element);
         }
+    }
+    Ok(list::from_rc_vec(results))
+}
+
+/// `(reduce f initial list)` combines the elements of the list with `(f element accumulator)`
+/// from left to right, starting with the first element. The initial value is only used if the
+/// list is empty.
+fn reduce(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 3)?;
+    let mut elements = list_arg(&args[2])?.into_iter();
+    let Some(first) = elements.next() else {
+        return Ok(args[1].clone());
     };
-    Ok(Rc::new(result))
+    elements.try_fold(first, |acc, element| {
+        call(runtime, &args[0], vec![element, acc])
+    })
 }
 
-fn mul(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
-    use crate::value::{Atom::*, Value::*};
-    check_args_length(args, 2)?;
-    let (lhs, rhs) = list::take2(args.clone());
-    let result = match (&*lhs, &*rhs) {
-        (Atom(I64(lhs)), Atom(I64(rhs))) => Atom(I64(lhs * rhs)),
-        _ => {
-            return Err(RuntimeError::Message(format!(
-                "incorrect types in arguments to mul: {}",
-                args
-            )))
+/// `(fold-left f initial list ...)` combines the initial value with the corresponding elements
+/// of each list from left to right, as `(f accumulator element ...)`
+fn fold_left(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(args, Arity::at_least(3))?;
+    zip_lists(&args[2..])?
+        .into_iter()
+        .try_fold(args[1].clone(), |acc, mut elements| {
+            elements.insert(0, acc);
+            call(runtime, &args[0], elements)
+        })
+}
+
+/// `(fold-right f initial list ...)` combines the corresponding elements of each list with the
+/// initial value from right to left, as `(f element ... accumulator)`
+fn fold_right(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(args, Arity::at_least(3))?;
+    zip_lists(&args[2..])?
+        .into_iter()
+        .rev()
+        .try_fold(args[1].clone(), |acc, mut elements| {
+            elements.push(acc);
+            call(runtime, &args[0], elements)
+        })
+}
+
+/// Splits the arguments of `apply` into the function and the list of arguments to call it with,
+/// which is made up of any arguments between the two followed by the elements of the last one
+pub fn spread_args(args: &Rc<Value>) -> Result<(Rc<Value>, Rc<Value>), RuntimeError> {
+    let mut args = check_args_arity(args, Arity::at_least(2))?;
+    let last = args.pop().expect("at least two arguments");
+    let function = args.remove(0);
+    args.extend(list_arg(&last)?);
+    Ok((function, list::from_rc_vec(args)))
+}
+
+/// Whether `a` and `b` are the same according to the optional comparison function, or `equal?`
+/// if there isn't one
+fn compare(
+    runtime: &mut Runtime,
+    comparison: Option<&Rc<Value>>,
+    a: &Rc<Value>,
+    b: &Rc<Value>,
+) -> Result<bool, RuntimeError> {
+    match comparison {
+        Some(comparison) => Ok(call(runtime, comparison, vec![a.clone(), b.clone()])?.is_true()),
+        None => Ok(Value::is_equal(a, b)),
+    }
+}
+
+/// `(assoc key alist [compare])` returns the first pair in the list whose car is `key`, or false
+fn assoc(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(
+        args,
+        Arity {
+            min: 2,
+            max: Some(3),
+        },
+    )?;
+    for entry in list_arg(&args[1])? {
+        if compare(runtime, args.get(2), &args[0], &pair_arg(&entry)?.car())? {
+            return Ok(entry);
+        }
+    }
+    Ok(Rc::new(Value::bool(false)))
+}
+
+/// `(member x list [compare])` returns the first tail of the list whose car is `x`, or false
+fn member(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(
+        args,
+        Arity {
+            min: 2,
+            max: Some(3),
+        },
+    )?;
+    let mut tail = args[1].clone();
+    while let Value::Pair(pair) = &*tail {
+        if compare(runtime, args.get(2), &args[0], &pair.car())? {
+            return Ok(tail);
+        }
+        let cdr = pair.cdr();
+        tail = cdr;
+    }
+    if tail.is_nil() {
+        Ok(Rc::new(Value::bool(false)))
+    } else {
+        Err(RuntimeErrorKind::NotAList(args[1].clone()).into())
+    }
+}
+
+/// A stable merge sort. The comparison function can't be relied on to be a total order, so the
+/// standard library's sorts aren't used.
+fn merge_sort(
+    runtime: &mut Runtime,
+    mut values: Vec<Rc<Value>>,
+    less: &Rc<Value>,
+) -> Result<Vec<Rc<Value>>, RuntimeError> {
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let left = merge_sort(runtime, values, less)?;
+    let right = merge_sort(runtime, right, less)?;
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // elements of the left half go first unless strictly greater, keeping the sort stable
+        if call(runtime, less, vec![r.clone(), l.clone()])?.is_true() {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
         }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+/// `(sort list less?)` sorts the list in ascending order of the comparison function
+fn sort(runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    let sorted = merge_sort(runtime, list_arg(&args[0])?, &args[1])?;
+    Ok(list::from_rc_vec(sorted))
+}
+
+/// Whether the single argument satisfies `f`
+fn predicate(args: &Rc<Value>, f: fn(&Rc<Value>) -> bool) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(Rc::new(Value::bool(f(&args[0]))))
+}
+
+fn is_null(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| value.is_nil())
+}
+
+fn is_pair(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| matches!(**value, Value::Pair(_)))
+}
+
+fn is_list(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, list::is_list)
+}
+
+fn is_symbol(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| value.as_symbol().is_some())
+}
+
+fn is_string(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| {
+        matches!(**value, Value::Atom(Atom::String(_)))
+    })
+}
+
+fn is_number(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(
+        args,
+        |value| matches!(&**value, Value::Atom(atom) if Number::from_atom(atom).is_some()),
+    )
+}
+
+fn is_boolean(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| matches!(**value, Value::Atom(Atom::Bool(_))))
+}
+
+/// Macros can't be called as functions, so aren't procedures
+fn is_procedure(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| {
+        matches!(
+            **value,
+            Value::Function(Function::Lambda(_) | Function::BuiltIn(_))
+        )
+    })
+}
+
+/// Whether the two arguments are the same according to `f`
+fn equivalence(
+    args: &Rc<Value>,
+    f: fn(&Rc<Value>, &Rc<Value>) -> bool,
+) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    Ok(Rc::new(Value::bool(f(&args[0], &args[1]))))
+}
+
+fn is_eq(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    equivalence(args, Value::is_eq)
+}
+
+fn is_eqv(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    equivalence(args, Value::is_eqv)
+}
+
+fn is_equal(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    equivalence(args, Value::is_equal)
+}
+
+fn string_value<S: Into<String>>(s: S) -> Rc<Value> {
+    Rc::new(Value::string(s))
+}
+
+fn string_length(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let length = string_arg(&args[0])?.chars().count();
+    Ok(Rc::new(Value::i64(length as i64)))
+}
+
+/// `(substring s start [end])` is the characters of `s` from `start` up to but not including
+/// `end`, which defaults to the end of the string
+fn substring(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(
+        args,
+        Arity {
+            min: 2,
+            max: Some(3),
+        },
+    )?;
+    let chars = string_arg(&args[0])?.chars().collect::<Vec<_>>();
+    let start = index_arg(&args[1], &args[0])?;
+    let end = match args.get(2) {
+        Some(end) => index_arg(end, &args[0])?,
+        None => chars.len(),
     };
-    Ok(Rc::new(result))
+    let out_of_range = |index: &Rc<Value>| RuntimeErrorKind::IndexOutOfRange {
+        index: index.clone(),
+        value: args[0].clone(),
+    };
+    if end > chars.len() {
+        return Err(out_of_range(&args[2]).into());
+    }
+    if start > end {
+        return Err(out_of_range(&args[1]).into());
+    }
+    Ok(string_value(chars[start..end].iter().collect::<String>()))
 }
 
-fn eq(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
-    use crate::value::{Atom::*, Value::*};
-    check_args_length(args, 2)?;
-    let (lhs, rhs) = list::take2(args.clone());
-    let result = match (&*lhs, &*rhs) {
-        (Atom(I64(lhs)), Atom(I64(rhs))) => Atom(Bool(lhs == rhs)),
-        _ => {
-            return Err(RuntimeError::Message(format!(
-                "incorrect types in arguments to eq: {}",
-                args
-            )))
+fn string_append(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(args, Arity::at_least(0))?;
+    let strings = args.iter().map(string_arg).collect::<Result<Vec<_>, _>>()?;
+    Ok(string_value(strings.concat()))
+}
+
+fn string_ref(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    let index = index_arg(&args[1], &args[0])?;
+    let c = string_arg(&args[0])?.chars().nth(index).ok_or_else(|| {
+        RuntimeErrorKind::IndexOutOfRange {
+            index: args[1].clone(),
+            value: args[0].clone(),
         }
+    })?;
+    Ok(Rc::new(Value::char(c)))
+}
+
+/// `(string-split s [separator])` splits `s` at each occurrence of the separator, or into words
+/// separated by whitespace if there isn't one
+fn string_split(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(
+        args,
+        Arity {
+            min: 1,
+            max: Some(2),
+        },
+    )?;
+    let s = string_arg(&args[0])?;
+    let parts = match args.get(1) {
+        Some(separator) => s.split(pattern_arg(separator)?).map(string_value).collect(),
+        None => s.split_whitespace().map(string_value).collect(),
+    };
+    Ok(list::from_rc_vec(parts))
+}
+
+/// `(string-join strings [separator])` concatenates a list of strings, separated by a space if no
+/// separator is given
+fn string_join(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_arity(
+        args,
+        Arity {
+            min: 1,
+            max: Some(2),
+        },
+    )?;
+    let strings = list_arg(&args[0])?;
+    let strings = strings
+        .iter()
+        .map(string_arg)
+        .collect::<Result<Vec<_>, _>>()?;
+    let separator = match args.get(1) {
+        Some(separator) => string_arg(separator)?,
+        None => " ",
+    };
+    Ok(string_value(strings.join(separator)))
+}
+
+fn string_upcase(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(string_value(string_arg(&args[0])?.to_uppercase()))
+}
+
+fn string_downcase(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(string_value(string_arg(&args[0])?.to_lowercase()))
+}
+
+/// Removes whitespace from both ends
+fn string_trim(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(string_value(string_arg(&args[0])?.trim()))
+}
+
+/// `(string-contains s pattern)` is the index of the first occurrence of the pattern in `s`, or
+/// false if there isn't one
+fn string_contains(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 2)?;
+    let s = string_arg(&args[0])?;
+    let result = match s.find(string_arg(&args[1])?) {
+        Some(byte_index) => Value::i64(s[..byte_index].chars().count() as i64),
+        None => Value::bool(false),
     };
     Ok(Rc::new(result))
 }
 
-fn println(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
-    check_args_length(args, 1)?;
-    let arg = list::head(args);
-    println!("{}", arg);
-    Ok(Rc::new(Value::nil()))
+/// `(string-replace s pattern replacement)` replaces every occurrence of the pattern in `s`
+fn string_replace(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 3)?;
+    let s = string_arg(&args[0])?;
+    Ok(string_value(
+        s.replace(pattern_arg(&args[1])?, string_arg(&args[2])?),
+    ))
+}
+
+fn string_to_list(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let chars = string_arg(&args[0])?
+        .chars()
+        .map(|c| Rc::new(Value::char(c)))
+        .collect();
+    Ok(list::from_rc_vec(chars))
+}
+
+fn string_to_symbol(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(Rc::new(Value::symbol(string_arg(&args[0])?)))
+}
+
+fn symbol_to_string(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let name = args[0]
+        .syntactic_name()
+        .ok_or_else(|| RuntimeErrorKind::TypeMismatch {
+            expected: "symbol",
+            found: args[0].clone(),
+        })?;
+    Ok(string_value(name))
+}
+
+fn number_to_string(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    number_arg(&args[0])?;
+    Ok(string_value(args[0].to_string()))
+}
+
+/// Reads a number written as it would be in source code, returning false if the string isn't
+/// one
+fn string_to_number(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let number = match parse::parse(string_arg(&args[0])?).as_deref() {
+        Ok([Value::Atom(atom)]) => Number::from_atom(atom),
+        _ => None,
+    };
+    Ok(Rc::new(match number {
+        Some(number) => number.into_value(),
+        None => Value::bool(false),
+    }))
+}
+
+fn is_char(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    predicate(args, |value| matches!(**value, Value::Atom(Atom::Char(_))))
+}
+
+fn char_to_integer(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(Rc::new(Value::i64(char_arg(&args[0])? as i64)))
+}
+
+/// The character with a Unicode code point
+fn integer_to_char(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let code = match &*args[0] {
+        Value::Atom(Atom::I64(i)) => u32::try_from(*i).ok(),
+        _ => None,
+    };
+    let c = code
+        .and_then(char::from_u32)
+        .ok_or_else(|| RuntimeErrorKind::TypeMismatch {
+            expected: "character code",
+            found: args[0].clone(),
+        })?;
+    Ok(Rc::new(Value::char(c)))
+}
+
+/// Whether the single character argument satisfies `f`
+fn char_predicate(args: &Rc<Value>, f: fn(char) -> bool) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    Ok(Rc::new(Value::bool(f(char_arg(&args[0])?))))
+}
+
+fn is_char_alphabetic(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    char_predicate(args, char::is_alphabetic)
+}
+
+fn is_char_numeric(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    char_predicate(args, char::is_numeric)
+}
+
+fn is_char_whitespace(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    char_predicate(args, char::is_whitespace)
+}
+
+/// Converts the case of a character with `f`, leaving it unchanged if the result isn't a single
+/// character (as with the uppercase of `ß`)
+fn convert_case<I: Iterator<Item = char>>(
+    args: &Rc<Value>,
+    f: fn(char) -> I,
+) -> Result<Rc<Value>, RuntimeError> {
+    let args = check_args_length(args, 1)?;
+    let c = char_arg(&args[0])?;
+    let mut converted = f(c);
+    let c = match (converted.next(), converted.next()) {
+        (Some(converted), None) => converted,
+        _ => c,
+    };
+    Ok(Rc::new(Value::char(c)))
+}
+
+fn char_upcase(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    convert_case(args, char::to_uppercase)
+}
+
+fn char_downcase(args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    convert_case(args, char::to_lowercase)
 }
 
 impl BuiltIn {
@@ -112,20 +1194,289 @@ impl BuiltIn {
             ADD => Some(Add),
             SUB => Some(Sub),
             MUL => Some(Mul),
+            DIV => Some(Div),
             EQ => Some(Eq),
+            LT => Some(Lt),
+            GT => Some(Gt),
+            LE => Some(Le),
+            GE => Some(Ge),
+            QUOTIENT => Some(Quotient),
+            REMAINDER => Some(Remainder),
+            MODULO => Some(Modulo),
+            ABS => Some(Abs),
+            MIN => Some(Min),
+            MAX => Some(Max),
+            SQRT => Some(Sqrt),
+            EXPT => Some(Expt),
+            EXP => Some(Exp),
+            LOG => Some(Log),
+            SIN => Some(Sin),
+            COS => Some(Cos),
+            TAN => Some(Tan),
+            ASIN => Some(Asin),
+            ACOS => Some(Acos),
+            ATAN => Some(Atan),
+            FLOOR => Some(Floor),
+            CEILING => Some(Ceiling),
+            ROUND => Some(Round),
+            TRUNCATE => Some(Truncate),
+            EXACT_TO_INEXACT => Some(ExactToInexact),
+            NUMERATOR => Some(Numerator),
+            DENOMINATOR => Some(Denominator),
             PRINTLN => Some(Println),
-            _ => None,
+            SET_CAR => Some(SetCar),
+            SET_CDR => Some(SetCdr),
+            CAR => Some(Car),
+            CDR => Some(Cdr),
+            CONS => Some(Cons),
+            LIST => Some(List),
+            LENGTH => Some(Length),
+            APPEND => Some(Append),
+            REVERSE => Some(Reverse),
+            LIST_REF => Some(ListRef),
+            LIST_TAIL => Some(ListTail),
+            MAP => Some(Map),
+            FOR_EACH => Some(ForEach),
+            FILTER => Some(Filter),
+            REDUCE => Some(Reduce),
+            FOLD_LEFT => Some(FoldLeft),
+            FOLD_RIGHT => Some(FoldRight),
+            APPLY => Some(Apply),
+            ASSOC => Some(Assoc),
+            MEMBER => Some(Member),
+            SORT => Some(Sort),
+            IS_NULL => Some(IsNull),
+            IS_PAIR => Some(IsPair),
+            IS_LIST => Some(IsList),
+            IS_SYMBOL => Some(IsSymbol),
+            IS_STRING => Some(IsString),
+            IS_NUMBER => Some(IsNumber),
+            IS_BOOLEAN => Some(IsBoolean),
+            IS_PROCEDURE => Some(IsProcedure),
+            IS_EQ => Some(IsEq),
+            IS_EQV => Some(IsEqv),
+            IS_EQUAL => Some(IsEqual),
+            STRING_LENGTH => Some(StringLength),
+            SUBSTRING => Some(Substring),
+            STRING_APPEND => Some(StringAppend),
+            STRING_REF => Some(StringRef),
+            STRING_SPLIT => Some(StringSplit),
+            STRING_JOIN => Some(StringJoin),
+            STRING_UPCASE => Some(StringUpcase),
+            STRING_DOWNCASE => Some(StringDowncase),
+            STRING_TRIM => Some(StringTrim),
+            STRING_CONTAINS => Some(StringContains),
+            STRING_REPLACE => Some(StringReplace),
+            STRING_TO_LIST => Some(StringToList),
+            STRING_TO_SYMBOL => Some(StringToSymbol),
+            SYMBOL_TO_STRING => Some(SymbolToString),
+            NUMBER_TO_STRING => Some(NumberToString),
+            STRING_TO_NUMBER => Some(StringToNumber),
+            IS_CHAR => Some(IsChar),
+            CHAR_TO_INTEGER => Some(CharToInteger),
+            INTEGER_TO_CHAR => Some(IntegerToChar),
+            IS_CHAR_ALPHABETIC => Some(IsCharAlphabetic),
+            IS_CHAR_NUMERIC => Some(IsCharNumeric),
+            IS_CHAR_WHITESPACE => Some(IsCharWhitespace),
+            CHAR_UPCASE => Some(CharUpcase),
+            CHAR_DOWNCASE => Some(CharDowncase),
+            _ => CXRS.iter().find(|&&name| name == s).map(|name| Cxr(name)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        use name::*;
+        use BuiltIn::*;
+        match self {
+            Add => ADD,
+            Sub => SUB,
+            Mul => MUL,
+            Div => DIV,
+            Eq => EQ,
+            Lt => LT,
+            Gt => GT,
+            Le => LE,
+            Ge => GE,
+            Quotient => QUOTIENT,
+            Remainder => REMAINDER,
+            Modulo => MODULO,
+            Abs => ABS,
+            Min => MIN,
+            Max => MAX,
+            Sqrt => SQRT,
+            Expt => EXPT,
+            Exp => EXP,
+            Log => LOG,
+            Sin => SIN,
+            Cos => COS,
+            Tan => TAN,
+            Asin => ASIN,
+            Acos => ACOS,
+            Atan => ATAN,
+            Floor => FLOOR,
+            Ceiling => CEILING,
+            Round => ROUND,
+            Truncate => TRUNCATE,
+            ExactToInexact => EXACT_TO_INEXACT,
+            Numerator => NUMERATOR,
+            Denominator => DENOMINATOR,
+            Println => PRINTLN,
+            SetCar => SET_CAR,
+            SetCdr => SET_CDR,
+            Car => CAR,
+            Cdr => CDR,
+            Cxr(name) => name,
+            Cons => CONS,
+            List => LIST,
+            Length => LENGTH,
+            Append => APPEND,
+            Reverse => REVERSE,
+            ListRef => LIST_REF,
+            ListTail => LIST_TAIL,
+            Map => MAP,
+            ForEach => FOR_EACH,
+            Filter => FILTER,
+            Reduce => REDUCE,
+            FoldLeft => FOLD_LEFT,
+            FoldRight => FOLD_RIGHT,
+            Apply => APPLY,
+            Assoc => ASSOC,
+            Member => MEMBER,
+            Sort => SORT,
+            IsNull => IS_NULL,
+            IsPair => IS_PAIR,
+            IsList => IS_LIST,
+            IsSymbol => IS_SYMBOL,
+            IsString => IS_STRING,
+            IsNumber => IS_NUMBER,
+            IsBoolean => IS_BOOLEAN,
+            IsProcedure => IS_PROCEDURE,
+            IsEq => IS_EQ,
+            IsEqv => IS_EQV,
+            IsEqual => IS_EQUAL,
+            StringLength => STRING_LENGTH,
+            Substring => SUBSTRING,
+            StringAppend => STRING_APPEND,
+            StringRef => STRING_REF,
+            StringSplit => STRING_SPLIT,
+            StringJoin => STRING_JOIN,
+            StringUpcase => STRING_UPCASE,
+            StringDowncase => STRING_DOWNCASE,
+            StringTrim => STRING_TRIM,
+            StringContains => STRING_CONTAINS,
+            StringReplace => STRING_REPLACE,
+            StringToList => STRING_TO_LIST,
+            StringToSymbol => STRING_TO_SYMBOL,
+            SymbolToString => SYMBOL_TO_STRING,
+            NumberToString => NUMBER_TO_STRING,
+            StringToNumber => STRING_TO_NUMBER,
+            IsChar => IS_CHAR,
+            CharToInteger => CHAR_TO_INTEGER,
+            IntegerToChar => INTEGER_TO_CHAR,
+            IsCharAlphabetic => IS_CHAR_ALPHABETIC,
+            IsCharNumeric => IS_CHAR_NUMERIC,
+            IsCharWhitespace => IS_CHAR_WHITESPACE,
+            CharUpcase => CHAR_UPCASE,
+            CharDowncase => CHAR_DOWNCASE,
         }
     }
 
-    pub fn eval(&self, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
+    pub fn eval(&self, runtime: &mut Runtime, args: &Rc<Value>) -> Result<Rc<Value>, RuntimeError> {
         use BuiltIn::*;
         match self {
             Add => add(args),
             Sub => sub(args),
             Mul => mul(args),
+            Div => div(args),
             Eq => eq(args),
+            Lt => lt(args),
+            Gt => gt(args),
+            Le => le(args),
+            Ge => ge(args),
+            Quotient => quotient(args),
+            Remainder => remainder(args),
+            Modulo => modulo(args),
+            Abs => abs(args),
+            Min => min(args),
+            Max => max(args),
+            Sqrt => sqrt(args),
+            Expt => expt(args),
+            Exp => exp(args),
+            Log => log(args),
+            Sin => sin(args),
+            Cos => cos(args),
+            Tan => tan(args),
+            Asin => asin(args),
+            Acos => acos(args),
+            Atan => atan(args),
+            Floor => floor(args),
+            Ceiling => ceiling(args),
+            Round => round(args),
+            Truncate => truncate(args),
+            ExactToInexact => exact_to_inexact(args),
+            Numerator => numerator(args),
+            Denominator => denominator(args),
             Println => println(args),
+            SetCar => set_car(args),
+            SetCdr => set_cdr(args),
+            Car => car(args),
+            Cdr => cdr(args),
+            Cxr(name) => cxr(name, args),
+            Cons => cons(args),
+            List => list(args),
+            Length => length(args),
+            Append => append(args),
+            Reverse => reverse(args),
+            ListRef => list_ref(args),
+            ListTail => list_tail(args),
+            Map => map(runtime, args),
+            ForEach => for_each(runtime, args),
+            Filter => filter(runtime, args),
+            Reduce => reduce(runtime, args),
+            FoldLeft => fold_left(runtime, args),
+            FoldRight => fold_right(runtime, args),
+            Apply => {
+                let (function, args) = spread_args(args)?;
+                runtime.call(&function, &args)
+            }
+            Assoc => assoc(runtime, args),
+            Member => member(runtime, args),
+            Sort => sort(runtime, args),
+            IsNull => is_null(args),
+            IsPair => is_pair(args),
+            IsList => is_list(args),
+            IsSymbol => is_symbol(args),
+            IsString => is_string(args),
+            IsNumber => is_number(args),
+            IsBoolean => is_boolean(args),
+            IsProcedure => is_procedure(args),
+            IsEq => is_eq(args),
+            IsEqv => is_eqv(args),
+            IsEqual => is_equal(args),
+            StringLength => string_length(args),
+            Substring => substring(args),
+            StringAppend => string_append(args),
+            StringRef => string_ref(args),
+            StringSplit => string_split(args),
+            StringJoin => string_join(args),
+            StringUpcase => string_upcase(args),
+            StringDowncase => string_downcase(args),
+            StringTrim => string_trim(args),
+            StringContains => string_contains(args),
+            StringReplace => string_replace(args),
+            StringToList => string_to_list(args),
+            StringToSymbol => string_to_symbol(args),
+            SymbolToString => symbol_to_string(args),
+            NumberToString => number_to_string(args),
+            StringToNumber => string_to_number(args),
+            IsChar => is_char(args),
+            CharToInteger => char_to_integer(args),
+            IntegerToChar => integer_to_char(args),
+            IsCharAlphabetic => is_char_alphabetic(args),
+            IsCharNumeric => is_char_numeric(args),
+            IsCharWhitespace => is_char_whitespace(args),
+            CharUpcase => char_upcase(args),
+            CharDowncase => char_downcase(args),
         }
     }
 }
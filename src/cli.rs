@@ -0,0 +1,115 @@
+use std::{fmt, path::PathBuf};
+
+pub const USAGE: &str = "\
+usage: lisp1 [OPTIONS] [FILE...] [-- ARG...]
+
+Runs each FILE in turn in a single runtime. If no files or expressions are
+given, starts an interactive session when stdin is a terminal and otherwise
+reads the program from stdin.
+
+options:
+  -e EXPR     evaluate EXPR (may be given multiple times, and mixed with files)
+  -           read a program from stdin
+  -i          start an interactive session after running any other inputs
+  -h, --help  print this message
+
+Arguments following -- are available to the program as the list of strings
+`command-line-arguments`.";
+
+/// A program to run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    File(PathBuf),
+    Stdin,
+    Expr(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub inputs: Vec<Input>,
+    pub script_args: Vec<String>,
+    pub interactive: bool,
+    pub help: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    MissingExpr,
+    UnknownOption(String),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingExpr => write!(f, "-e requires an expression"),
+            Self::UnknownOption(option) => write!(f, "unknown option: {}", option),
+        }
+    }
+}
+
+impl Args {
+    /// Parses command line arguments, not including the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, ArgsError> {
+        let mut ret = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => {
+                    ret.script_args = args.collect();
+                    break;
+                }
+                "-" => ret.inputs.push(Input::Stdin),
+                "-e" => ret
+                    .inputs
+                    .push(Input::Expr(args.next().ok_or(ArgsError::MissingExpr)?)),
+                "-i" => ret.interactive = true,
+                "-h" | "--help" => ret.help = true,
+                _ if arg.starts_with('-') => return Err(ArgsError::UnknownOption(arg)),
+                _ => ret.inputs.push(Input::File(PathBuf::from(arg))),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Args, ArgsError, Input};
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn inputs() {
+        assert_eq!(parse(&[]).unwrap().inputs, vec![]);
+        assert_eq!(
+            parse(&["a.lisp", "-e", "(println 1)", "-", "b.lisp"])
+                .unwrap()
+                .inputs,
+            vec![
+                Input::File(PathBuf::from("a.lisp")),
+                Input::Expr("(println 1)".to_string()),
+                Input::Stdin,
+                Input::File(PathBuf::from("b.lisp")),
+            ]
+        );
+    }
+
+    #[test]
+    fn script_args() {
+        let args = parse(&["a.lisp", "--", "-e", "foo"]).unwrap();
+        assert_eq!(args.inputs, vec![Input::File(PathBuf::from("a.lisp"))]);
+        assert_eq!(args.script_args, vec!["-e".to_string(), "foo".to_string()]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(&["-e"]), Err(ArgsError::MissingExpr));
+        assert_eq!(
+            parse(&["-x"]),
+            Err(ArgsError::UnknownOption("-x".to_string()))
+        );
+    }
+}
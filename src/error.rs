@@ -1,4 +1,151 @@
-#[derive(Debug)]
-pub enum RuntimeError {
-    Message(String),
+use crate::{language::Keyword, parse::ParseError, source::Span, value::Value};
+use std::{fmt, rc::Rc};
+
+/// The range of numbers of arguments accepted by a function
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
 }
+
+impl Arity {
+    pub const fn exactly(n: usize) -> Self {
+        Self {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    pub const fn at_least(n: usize) -> Self {
+        Self { min: n, max: None }
+    }
+
+    pub fn contains(self, n: usize) -> bool {
+        n >= self.min && self.max.is_none_or(|max| n <= max)
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuntimeErrorKind {
+    UnboundVariable(String),
+    UninitializedVariable(String),
+    ArityMismatch {
+        expected: Arity,
+        found: usize,
+    },
+    TypeMismatch {
+        expected: &'static str,
+        found: Rc<Value>,
+    },
+    NotCallable(Rc<Value>),
+    NotAList(Rc<Value>),
+    MalformedSpecialForm {
+        keyword: Keyword,
+        form: Rc<Value>,
+    },
+    DefineInExpressionContext,
+    UnquoteOutsideQuasiquote(Keyword),
+    NoMatchingSyntaxRule(Rc<Value>),
+    DivisionByZero,
+    ExponentTooLarge,
+    IndexOutOfRange {
+        index: Rc<Value>,
+        value: Rc<Value>,
+    },
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RuntimeErrorKind::*;
+        match self {
+            UnboundVariable(name) => write!(f, "unbound variable: {}", name),
+            UninitializedVariable(name) => {
+                write!(f, "variable used before it was initialized: {}", name)
+            }
+            ArityMismatch { expected, found } => {
+                write!(f, "expected {} arguments, found {}", expected, found)
+            }
+            TypeMismatch { expected, found } => write!(f, "expected {}, found {}", expected, found),
+            NotCallable(value) => write!(f, "value cannot be called: {}", value),
+            NotAList(value) => write!(f, "not a list: {}", value),
+            MalformedSpecialForm { keyword, form } => {
+                write!(f, "malformed {}: {}", keyword.as_str(), form)
+            }
+            DefineInExpressionContext => write!(f, "define used where a value is required"),
+            UnquoteOutsideQuasiquote(keyword) => {
+                write!(f, "{} used outside of quasiquote", keyword.as_str())
+            }
+            NoMatchingSyntaxRule(form) => write!(f, "no syntax rule matches: {}", form),
+            DivisionByZero => write!(f, "division by zero"),
+            ExponentTooLarge => write!(f, "exponent too large for an exact result"),
+            IndexOutOfRange { index, value } => {
+                write!(f, "index {} out of range: {}", index, value)
+            }
+        }
+    }
+}
+
+/// An error encountered while evaluating a program. The span is the location of the innermost
+/// form being evaluated when the error occurred, for forms whose location is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub span: Option<Span>,
+}
+
+impl From<RuntimeErrorKind> for RuntimeError {
+    fn from(kind: RuntimeErrorKind) -> Self {
+        Self { kind, span: None }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{}: {}\n{}", span, self.kind, span.excerpt()),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// Any error that can occur while running a program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    Runtime(RuntimeError),
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(error: RuntimeError) -> Self {
+        Self::Runtime(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => error.fmt(f),
+            Self::Runtime(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
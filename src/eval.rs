@@ -1,10 +1,19 @@
 use crate::{
-    built_in::BuiltIn,
-    language::Keyword,
+    built_in::{self, BuiltIn},
+    error::{Arity, Error, RuntimeError, RuntimeErrorKind},
+    language::{self, clause, Keyword},
     list,
-    value::{Atom, Function, Pair, Value},
+    parse::{self, ParseError},
+    source::{Source, SourceMap},
+    syntax_rules::SyntaxRules,
+    value::{Atom, Function, Value},
+};
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt,
+    rc::{Rc, Weak},
 };
-use std::{collections::HashMap, rc::Rc};
 
 // (define fact1 (lambda (n)
 //   (if (= n 0)
@@ -27,226 +36,1254 @@ use std::{collections::HashMap, rc::Rc};
 //   - scope hierarchy
 //   - names of captured variables
 
-#[derive(Debug)]
+#[cfg(test)]
+thread_local! {
+    /// The number of scopes currently allocated on this thread, and the most that have been
+    /// allocated at once, for checking that scopes are freed in tests
+    static LIVE_SCOPE_COUNT: std::cell::Cell<(usize, usize)> =
+        const { std::cell::Cell::new((0, 0)) };
+}
+
+/// A set of variable bindings, nested within the scope it was created in. Scopes are shared by
+/// the lambdas which capture them, and are freed once the last such lambda is dropped. A lambda
+/// stored in the very scope it captures forms a cycle, which is freed by
+/// `Runtime::collect_cycles`. Variables which have been declared (by a `letrec`) but not yet
+/// initialised have no value.
 pub struct Scope {
-    variables_by_name: HashMap<String, Rc<Value>>,
+    variables_by_name: RefCell<HashMap<String, Option<Rc<Value>>>>,
+    parent: Option<Rc<Scope>>,
 }
 
 impl Scope {
-    pub fn new() -> Self {
-        Self {
-            variables_by_name: HashMap::default(),
+    pub fn new(parent: Option<Rc<Scope>>) -> Rc<Self> {
+        #[cfg(test)]
+        LIVE_SCOPE_COUNT.with(|count| {
+            let (live, peak) = count.get();
+            count.set((live + 1, peak.max(live + 1)));
+        });
+        Rc::new(Self {
+            variables_by_name: RefCell::new(HashMap::default()),
+            parent,
+        })
+    }
+
+    pub fn define_variable(&self, name: &str, value: Rc<Value>) {
+        self.variables_by_name
+            .borrow_mut()
+            .insert(name.to_string(), Some(value));
+    }
+
+    fn declare_variable(&self, name: &str) {
+        self.variables_by_name
+            .borrow_mut()
+            .insert(name.to_string(), None);
+    }
+
+    /// Replaces the value of the variable in the nearest scope which defines it, returning false
+    /// if no scope does
+    fn set_variable(&self, name: &str, value: Rc<Value>) -> bool {
+        let mut scope = self;
+        loop {
+            if let Some(variable) = scope.variables_by_name.borrow_mut().get_mut(name) {
+                *variable = Some(value);
+                return true;
+            }
+            match scope.parent.as_deref() {
+                Some(parent) => scope = parent,
+                None => return false,
+            }
         }
     }
 
-    pub fn define_variable(&mut self, name: &str, value: Rc<Value>) {
-        self.variables_by_name.insert(name.to_string(), value);
+    /// Looks up a variable in the nearest scope which declares it, returning `Some(None)` if the
+    /// variable is declared but not yet initialised
+    fn resolve_name(&self, name: &str) -> Option<Option<Rc<Value>>> {
+        let mut scope = self;
+        loop {
+            if let Some(value) = scope.variables_by_name.borrow().get(name) {
+                return Some(value.clone());
+            }
+            scope = scope.parent.as_deref()?;
+        }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct ScopePath {
-    indices: Vec<usize>,
+/// A value or scope visited while looking for unreachable cycles
+#[derive(Clone)]
+enum Node {
+    Value(Rc<Value>),
+    Scope(Rc<Scope>),
 }
 
-impl ScopePath {
-    fn new() -> Self {
-        Self { indices: vec![0] }
+impl Node {
+    fn address(&self) -> *const () {
+        match self {
+            Self::Value(value) => Rc::as_ptr(value) as *const (),
+            Self::Scope(scope) => Rc::as_ptr(scope) as *const (),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Self::Value(value) => Rc::strong_count(value),
+            Self::Scope(scope) => Rc::strong_count(scope),
+        }
+    }
+
+    /// The values and scopes referenced by this node. References held behind another `Rc`
+    /// (such as the scope of a `syntax-rules` macro) aren't followed, which can only make a node
+    /// appear to be referenced from outside. The references of `opaque` aren't followed either.
+    fn children(&self, opaque: &Rc<Scope>) -> Vec<Node> {
+        match self {
+            Self::Scope(scope) if Rc::ptr_eq(scope, opaque) => Vec::new(),
+            Self::Scope(scope) => scope
+                .parent
+                .iter()
+                .cloned()
+                .map(Node::Scope)
+                .chain(
+                    scope
+                        .variables_by_name
+                        .borrow()
+                        .values()
+                        .flatten()
+                        .cloned()
+                        .map(Node::Value),
+                )
+                .collect(),
+            Self::Value(value) => match &**value {
+                Value::Pair(pair) => vec![Node::Value(pair.car()), Node::Value(pair.cdr())],
+                Value::Function(Function::Lambda(lambda) | Function::Macro(lambda)) => vec![
+                    Node::Value(lambda.body.clone()),
+                    Node::Scope(lambda.scope.clone()),
+                ],
+                Value::Identifier(identifier) => vec![
+                    Node::Value(identifier.symbol.clone()),
+                    Node::Scope(identifier.scope.clone()),
+                ],
+                Value::Atom(_) | Value::Function(_) => Vec::new(),
+            },
+        }
     }
+}
 
-    fn push(&self, index: usize) -> Self {
-        let mut ret = self.clone();
-        ret.indices.push(index);
-        ret
+#[cfg(test)]
+impl Drop for Scope {
+    fn drop(&mut self) {
+        LIVE_SCOPE_COUNT.with(|count| {
+            let (live, peak) = count.get();
+            count.set((live - 1, peak));
+        });
     }
+}
+
+/// The parameters of a lambda: required ones, then optional ones (with an expression giving the
+/// default value of each, if any) and finally one bound to a list of any remaining arguments
+#[derive(Debug, PartialEq, Eq)]
+struct Parameters {
+    required: Vec<String>,
+    optional: Vec<(String, Option<Rc<Value>>)>,
+    rest: Option<String>,
+}
 
-    fn iter(&self) -> impl '_ + Iterator<Item = usize> {
-        self.indices.iter().rev().cloned()
+impl Parameters {
+    fn arity(&self) -> Arity {
+        Arity {
+            min: self.required.len(),
+            max: match self.rest {
+                Some(_) => None,
+                None => Some(self.required.len() + self.optional.len()),
+            },
+        }
     }
 
-    fn current(&self) -> usize {
-        *self.indices.last().unwrap()
+    /// Parses a parameter list such as `(a b #!optional (c 1) . rest)`, or a single symbol
+    /// which is bound to a list of all the arguments
+    fn parse(value: &Rc<Value>) -> Option<Self> {
+        let (values, end) = list::to_vec_with_end(value);
+        let mut parameters = Self {
+            required: Vec::new(),
+            optional: Vec::new(),
+            rest: match &*end {
+                Value::Atom(Atom::Nil) => None,
+                _ => Some(end.as_symbol()?.to_string()),
+            },
+        };
+        let mut values = values.into_iter();
+        for value in values.by_ref() {
+            if value.syntactic_name() == Some(language::OPTIONAL) {
+                break;
+            }
+            parameters.required.push(value.as_symbol()?.to_string());
+        }
+        for value in values {
+            let optional = match list::to_vec(&*value).as_deref() {
+                Some([name, default]) => (name.as_symbol()?.to_string(), Some(default.clone())),
+                _ => (value.as_symbol()?.to_string(), None),
+            };
+            parameters.optional.push(optional);
+        }
+        Some(parameters)
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct Lambda {
-    argument_names: Vec<String>,
-    code_ast: Rc<Value>,
-    scope_path: ScopePath,
+    parameters: Rc<Parameters>,
+    /// The non-empty list of forms making up the body
+    body: Rc<Value>,
+    scope: Rc<Scope>,
+    /// A name bound to the lambda itself in the scope of each call (used by named `let`). This
+    /// lets the lambda call itself without being stored in the scope it captures.
+    self_name: Option<String>,
+}
+
+// Lambdas are compared by identity. Comparing captured scopes structurally wouldn't terminate
+// for recursive functions, which are stored in the scope they capture.
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameters == other.parameters
+            && Rc::ptr_eq(&self.body, &other.body)
+            && Rc::ptr_eq(&self.scope, &other.scope)
+    }
+}
+
+impl Eq for Lambda {}
+
+impl fmt::Debug for Lambda {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lambda")
+            .field("parameters", &self.parameters)
+            .field("body", &self.body)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The outcome of evaluating a single form. Forms whose result is that of another form in tail
+/// position return that form rather than evaluating it, to be evaluated by the caller.
+enum Step {
+    Return(Option<Rc<Value>>),
+    Eval(Rc<Value>, Rc<Scope>),
+}
+
+/// Splits a form such as `(unquote x)` into its keyword and argument
+fn prefix_form(value: &Value) -> Option<(Keyword, Rc<Value>)> {
+    let (op, args) = list::split_head(value)?;
+    let keyword = op.syntactic_name().and_then(Keyword::from_str)?;
+    match list::to_vec(args).as_deref() {
+        Some([arg]) => Some((keyword, arg.clone())),
+        _ => None,
+    }
+}
+
+/// Whether `value` is a non-empty list of forms, as required for the body of a lambda
+fn is_body(value: &Value) -> bool {
+    list::to_vec(value).is_some_and(|forms| !forms.is_empty())
 }
 
 pub struct Runtime {
-    scopes: Vec<Scope>,
+    global_scope: Rc<Scope>,
+    /// Every scope created by the runtime other than the global scope, which may be part of a
+    /// cycle
+    scopes: Vec<Weak<Scope>>,
+    /// The number of tracked scopes at which to next look for cycles
+    collect_at: usize,
+    source_map: SourceMap,
+    /// Used to give each identifier introduced by a macro expansion a unique name
+    next_alias: usize,
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // top-level functions are stored in the global scope which they capture, so break the
+        // cycles to allow the scope to be freed
+        self.global_scope.variables_by_name.borrow_mut().clear();
+    }
 }
 
 impl Runtime {
+    const MIN_COLLECT_AT: usize = 1024;
+
     pub fn new() -> Self {
-        let scopes = vec![Scope::new()];
-        Self { scopes }
+        Self {
+            global_scope: Scope::new(None),
+            scopes: Vec::new(),
+            collect_at: Self::MIN_COLLECT_AT,
+            source_map: SourceMap::new(),
+            next_alias: 0,
+        }
     }
 
-    fn make_scope(&mut self) -> usize {
-        let ret = self.scopes.len();
-        self.scopes.push(Scope::new());
-        ret
+    /// Creates a scope, occasionally freeing unreachable cycles of scopes first. Scopes are
+    /// checked once the number still alive has doubled, so the cost is amortised over the
+    /// scopes created.
+    fn new_scope(&mut self, parent: Rc<Scope>) -> Rc<Scope> {
+        if self.scopes.len() >= self.collect_at {
+            self.scopes.retain(|scope| scope.strong_count() > 0);
+            if self.scopes.len() >= self.collect_at / 2 {
+                self.collect_cycles();
+                self.scopes.retain(|scope| scope.strong_count() > 0);
+            }
+            self.collect_at = (self.scopes.len() * 2).max(Self::MIN_COLLECT_AT);
+        }
+        let scope = Scope::new(Some(parent));
+        self.scopes.push(Rc::downgrade(&scope));
+        scope
     }
 
-    fn resolve_name(&self, name: &str, scope_path: ScopePath) -> Option<Rc<Value>> {
-        for scope_index in scope_path.iter() {
-            if let Some(value) = self.scopes[scope_index].variables_by_name.get(name) {
-                return Some(value.clone());
+    /// Frees cycles of scopes and values which can no longer be reached. Any node referenced
+    /// more times than it is by the other nodes reachable from the tracked scopes is referenced
+    /// from outside (eg. by the global scope or the evaluator's stack), so is live along with
+    /// everything it references. The variables of the remaining scopes are cleared, breaking the
+    /// cycles between them.
+    pub fn collect_cycles(&mut self) {
+        let mut nodes = HashMap::<*const (), (Node, usize)>::new();
+        let mut stack = self
+            .scopes
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(Node::Scope)
+            .collect::<Vec<_>>();
+        for node in &stack {
+            nodes.insert(node.address(), (node.clone(), 0));
+        }
+        while let Some(node) = stack.pop() {
+            for child in node.children(&self.global_scope) {
+                match nodes.entry(child.address()) {
+                    Entry::Occupied(mut entry) => entry.get_mut().1 += 1,
+                    Entry::Vacant(entry) => {
+                        entry.insert((child.clone(), 1));
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        // the copy of each node in `nodes` accounts for one reference
+        let mut stack = nodes
+            .values()
+            .filter(|(node, internal)| node.strong_count() > internal + 1)
+            .map(|(node, _)| node.clone())
+            .collect::<Vec<_>>();
+        let mut live = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if live.insert(node.address()) {
+                stack.extend(node.children(&self.global_scope));
+            }
+        }
+        let garbage = nodes
+            .into_iter()
+            .filter(|(address, _)| !live.contains(address))
+            .filter_map(|(_, (node, _))| match node {
+                Node::Scope(scope) => Some(scope),
+                Node::Value(_) => None,
+            })
+            .collect::<Vec<_>>();
+        for scope in garbage {
+            let variables = std::mem::take(&mut *scope.variables_by_name.borrow_mut());
+            drop(variables);
+        }
+    }
+
+    /// Parses the text of `source`, remembering the location of each parsed value so that
+    /// errors encountered while evaluating them can report where they occurred
+    pub fn parse_source(&mut self, source: &Rc<Source>) -> Result<Vec<Rc<Value>>, ParseError> {
+        parse::parse_source(source, &mut self.source_map)
+    }
+
+    fn eval_define(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<(), RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::Define,
+            form: form.clone(),
+        };
+        let (name, value) = match list::to_vec(args).as_deref() {
+            Some([name, value]) => (name.clone(), value.clone()),
+            _ => return Err(malformed().into()),
+        };
+        let symbol_name = name.as_symbol().ok_or_else(malformed)?;
+        let result_value = self.eval_value(&value, scope)?;
+        scope.define_variable(symbol_name, result_value);
+        Ok(())
+    }
+
+    /// Looks up the value of a symbol, or of an identifier introduced by a macro expansion. An
+    /// identifier which isn't bound by the expansion refers to the symbol it was renamed from in
+    /// the scope where the macro was defined.
+    fn resolve_name(
+        &mut self,
+        name: &Rc<Value>,
+        scope: &Rc<Scope>,
+    ) -> Result<Rc<Value>, RuntimeError> {
+        let symbol_name = name.as_symbol().expect("resolving a non-symbol");
+        match scope.resolve_name(symbol_name) {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(RuntimeErrorKind::UninitializedVariable(
+                name.syntactic_name().unwrap_or(symbol_name).to_string(),
+            )
+            .into()),
+            None => match &**name {
+                Value::Identifier(identifier) => {
+                    self.resolve_name(&identifier.symbol, &identifier.scope)
+                }
+                _ => match BuiltIn::from_str(symbol_name) {
+                    Some(built_in) => Ok(Rc::new(Value::built_in(built_in))),
+                    None => Err(RuntimeErrorKind::UnboundVariable(symbol_name.to_string()).into()),
+                },
+            },
+        }
+    }
+
+    /// Like `resolve_name`, but replaces the value of the variable, returning false if it isn't
+    /// bound
+    fn set_name(name: &Value, scope: &Rc<Scope>, value: Rc<Value>) -> bool {
+        let symbol_name = name.as_symbol().expect("setting a non-symbol");
+        if scope.set_variable(symbol_name, value.clone()) {
+            return true;
+        }
+        match name {
+            Value::Identifier(identifier) => {
+                Self::set_name(&identifier.symbol, &identifier.scope, value)
             }
+            _ => false,
         }
-        None
     }
 
-    fn eval_define(&mut self, args: &Value, scope_path: ScopePath) {
-        let scope_index = scope_path.current();
-        match args {
-            Value::Pair(Pair(name, value)) => {
-                if let Value::Atom(Atom::Symbol(symbol_name)) = &**name {
-                    match &**value {
-                        Value::Pair(Pair(value, _rest_should_be_nil)) => {
-                            if let Some(result_value) = self.eval(value) {
-                                self.scopes[scope_index]
-                                    .define_variable(symbol_name.as_str(), result_value);
-                            } else {
-                                todo!()
-                            }
-                        }
-                        _ => todo!(),
-                    }
-                } else {
-                    todo!()
+    fn eval_set(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<(), RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::Set,
+            form: form.clone(),
+        };
+        let (name, value) = match list::to_vec(args).as_deref() {
+            Some([name, value]) => (name.clone(), value.clone()),
+            _ => return Err(malformed().into()),
+        };
+        let symbol_name = name.syntactic_name().ok_or_else(malformed)?;
+        let result_value = self.eval_value(&value, scope)?;
+        if Self::set_name(&name, scope, result_value) {
+            Ok(())
+        } else {
+            Err(RuntimeErrorKind::UnboundVariable(symbol_name.to_string()).into())
+        }
+    }
+
+    fn define_lambda(
+        &mut self,
+        keyword: Keyword,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Lambda, RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword,
+            form: form.clone(),
+        };
+        let (args, body) = match list::split_head(args) {
+            Some((args, body)) if is_body(&body) => (args, body),
+            _ => return Err(malformed().into()),
+        };
+        let parameters = Parameters::parse(&args).ok_or_else(malformed)?;
+        Ok(Lambda {
+            parameters: Rc::new(parameters),
+            body,
+            scope: scope.clone(),
+            self_name: None,
+        })
+    }
+
+    /// Binds the arguments of a call to a lambda, returning its body to be evaluated in tail
+    /// position
+    fn call_lambda(&mut self, lambda: &Lambda, args: &Rc<Value>) -> Result<Step, RuntimeError> {
+        let args_vec =
+            list::to_vec(args.clone()).ok_or_else(|| RuntimeErrorKind::NotAList(args.clone()))?;
+        let parameters = &lambda.parameters;
+        let arity = parameters.arity();
+        if !arity.contains(args_vec.len()) {
+            return Err(RuntimeErrorKind::ArityMismatch {
+                expected: arity,
+                found: args_vec.len(),
+            }
+            .into());
+        }
+        let arg_scope = self.new_scope(lambda.scope.clone());
+        if let Some(self_name) = lambda.self_name.as_ref() {
+            let value = Value::Function(Function::Lambda(lambda.clone()));
+            arg_scope.define_variable(self_name, Rc::new(value));
+        }
+        let mut args = args_vec.into_iter();
+        for (name, value) in parameters.required.iter().zip(args.by_ref()) {
+            arg_scope.define_variable(name.as_str(), value);
+        }
+        // defaults are evaluated in turn in the scope of the call, so can refer to the
+        // parameters before them. Optional parameters without a default are nil.
+        for (name, default) in parameters.optional.iter() {
+            let value = match (args.next(), default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => self.eval_value(default, &arg_scope)?,
+                (None, None) => Rc::new(Value::nil()),
+            };
+            arg_scope.define_variable(name.as_str(), value);
+        }
+        if let Some(rest) = parameters.rest.as_ref() {
+            arg_scope.define_variable(rest.as_str(), list::from_rc_vec(args.collect()));
+        }
+        self.eval_body(&lambda.body, &arg_scope)
+    }
+
+    /// Evaluates each form of a sequence in turn, returning the last one to be evaluated in tail
+    /// position
+    fn eval_sequence(
+        &mut self,
+        forms: &[Rc<Value>],
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        match forms.split_last() {
+            Some((last, init)) => {
+                for form in init {
+                    self.eval_in_scope(form, scope)?;
                 }
+                Ok(Step::Eval(last.clone(), scope.clone()))
+            }
+            None => Ok(Step::Return(Some(Rc::new(Value::nil())))),
+        }
+    }
+
+    /// Evaluates the body of a lambda or `let`-family form. The `define`s at the head of the body
+    /// are declared before any are evaluated, so they behave like the bindings of a `letrec*`.
+    fn eval_body(&mut self, body: &Rc<Value>, scope: &Rc<Scope>) -> Result<Step, RuntimeError> {
+        let forms =
+            list::to_vec(body.clone()).ok_or_else(|| RuntimeErrorKind::NotAList(body.clone()))?;
+        for form in forms.iter() {
+            let (op, args) = match list::split_head(&**form) {
+                Some((op, args)) => (op, args),
+                None => break,
+            };
+            match op.syntactic_name().and_then(Keyword::from_str) {
+                Some(Keyword::Define) => (),
+                _ => break,
+            }
+            if let Some(name) = list::head(&args).as_deref().and_then(Value::as_symbol) {
+                scope.declare_variable(name);
             }
-            _ => todo!(),
         }
+        self.eval_sequence(&forms, scope)
     }
 
-    fn define_lambda(&mut self, args: &Value, scope_path: ScopePath) -> Lambda {
-        let (args, code_ast) = list::take2(args);
-        let argument_name_values = list::to_vec(args);
-        let argument_names = argument_name_values
+    /// Parses the `((name init) ...)` bindings of a `let`-family form
+    fn let_bindings(
+        keyword: Keyword,
+        form: &Rc<Value>,
+        bindings: &Value,
+    ) -> Result<Vec<(String, Rc<Value>)>, RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword,
+            form: form.clone(),
+        };
+        list::to_vec(bindings)
+            .ok_or_else(malformed)?
             .into_iter()
-            .map(|value| match &*value {
-                Value::Atom(Atom::Symbol(s)) => s.clone(),
-                _ => panic!("expected symbol"),
+            .map(|binding| match list::to_vec(&*binding).as_deref() {
+                Some([name, init]) => name
+                    .as_symbol()
+                    .map(|name| (name.to_string(), init.clone()))
+                    .ok_or_else(malformed),
+                _ => Err(malformed()),
             })
-            .collect::<Vec<_>>();
-        Lambda {
-            argument_names,
-            code_ast,
-            scope_path: scope_path.push(self.make_scope()),
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Evaluates `let`, `let*`, `letrec`, `letrec*` and named `let` forms. The body is evaluated
+    /// in tail position in a new scope containing the bindings.
+    fn eval_let(
+        &mut self,
+        keyword: Keyword,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword,
+            form: form.clone(),
+        };
+        let (bindings, body) = list::split_head(args).ok_or_else(malformed)?;
+        if let (Keyword::Let, Some(name)) = (keyword, bindings.as_symbol()) {
+            let (bindings, body) = list::split_head(body).ok_or_else(malformed)?;
+            if !is_body(&body) {
+                return Err(malformed().into());
+            }
+            let (argument_names, inits): (Vec<_>, Vec<_>) =
+                Self::let_bindings(keyword, form, &bindings)?
+                    .into_iter()
+                    .unzip();
+            let lambda = Lambda {
+                parameters: Rc::new(Parameters {
+                    required: argument_names,
+                    optional: Vec::new(),
+                    rest: None,
+                }),
+                body,
+                scope: scope.clone(),
+                self_name: Some(name.to_string()),
+            };
+            let args = inits
+                .iter()
+                .map(|init| self.eval_value(init, scope))
+                .collect::<Result<Vec<_>, _>>()?;
+            return self.call_lambda(&lambda, &list::from_rc_vec(args));
+        }
+        if !is_body(&body) {
+            return Err(malformed().into());
+        }
+        let bindings = Self::let_bindings(keyword, form, &bindings)?;
+        match keyword {
+            Keyword::Let => {
+                let let_scope = self.new_scope(scope.clone());
+                for (name, init) in bindings {
+                    let value = self.eval_value(&init, scope)?;
+                    let_scope.define_variable(name.as_str(), value);
+                }
+                self.eval_body(&body, &let_scope)
+            }
+            Keyword::LetStar => {
+                let mut let_scope = scope.clone();
+                for (name, init) in bindings {
+                    let value = self.eval_value(&init, &let_scope)?;
+                    let_scope = self.new_scope(let_scope);
+                    let_scope.define_variable(name.as_str(), value);
+                }
+                let body_scope = self.new_scope(let_scope);
+                self.eval_body(&body, &body_scope)
+            }
+            Keyword::Letrec => {
+                let let_scope = self.new_scope(scope.clone());
+                for (name, _) in bindings.iter() {
+                    let_scope.declare_variable(name.as_str());
+                }
+                let values = bindings
+                    .iter()
+                    .map(|(_, init)| self.eval_value(init, &let_scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                for ((name, _), value) in bindings.iter().zip(values) {
+                    let_scope.define_variable(name.as_str(), value);
+                }
+                self.eval_body(&body, &let_scope)
+            }
+            Keyword::LetrecStar => {
+                let let_scope = self.new_scope(scope.clone());
+                for (name, _) in bindings.iter() {
+                    let_scope.declare_variable(name.as_str());
+                }
+                for (name, init) in bindings {
+                    let value = self.eval_value(&init, &let_scope)?;
+                    let_scope.define_variable(name.as_str(), value);
+                }
+                self.eval_body(&body, &let_scope)
+            }
+            _ => unreachable!("not a let form: {:?}", keyword),
+        }
+    }
+
+    fn eval_begin(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let forms = list::to_vec(args).ok_or_else(|| RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::Begin,
+            form: form.clone(),
+        })?;
+        self.eval_sequence(&forms, scope)
+    }
+
+    fn eval_if(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let (condition, if_true, if_false) = match list::to_vec(args).as_deref() {
+            Some([condition, if_true]) => (condition.clone(), if_true.clone(), None),
+            Some([condition, if_true, if_false]) => {
+                (condition.clone(), if_true.clone(), Some(if_false.clone()))
+            }
+            _ => {
+                return Err(RuntimeErrorKind::MalformedSpecialForm {
+                    keyword: Keyword::If,
+                    form: form.clone(),
+                }
+                .into())
+            }
+        };
+        if self.eval_value(&condition, scope)?.is_true() {
+            Ok(Step::Eval(if_true, scope.clone()))
+        } else if let Some(if_false) = if_false {
+            Ok(Step::Eval(if_false, scope.clone()))
+        } else {
+            Ok(Step::Return(Some(Rc::new(Value::nil()))))
+        }
+    }
+
+    /// Evaluates `when` and `unless` forms, whose body is evaluated if the condition is true or
+    /// false respectively
+    fn eval_when(
+        &mut self,
+        keyword: Keyword,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let (condition, body) = match list::to_vec(args).as_deref() {
+            Some([condition, body @ ..]) if !body.is_empty() => (condition.clone(), body.to_vec()),
+            _ => {
+                return Err(RuntimeErrorKind::MalformedSpecialForm {
+                    keyword,
+                    form: form.clone(),
+                }
+                .into())
+            }
+        };
+        if self.eval_value(&condition, scope)?.is_true() == (keyword == Keyword::When) {
+            self.eval_sequence(&body, scope)
+        } else {
+            Ok(Step::Return(Some(Rc::new(Value::nil()))))
+        }
+    }
+
+    /// Evaluates `and` and `or` forms, stopping at the first operand which is false or true
+    /// respectively. The last operand is evaluated in tail position.
+    fn eval_and_or(
+        &mut self,
+        keyword: Keyword,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let operands =
+            list::to_vec(args).ok_or_else(|| RuntimeErrorKind::MalformedSpecialForm {
+                keyword,
+                form: form.clone(),
+            })?;
+        let stop_when = keyword == Keyword::Or;
+        match operands.split_last() {
+            Some((last, init)) => {
+                for operand in init {
+                    let value = self.eval_value(operand, scope)?;
+                    if value.is_true() == stop_when {
+                        return Ok(Step::Return(Some(value)));
+                    }
+                }
+                Ok(Step::Eval(last.clone(), scope.clone()))
+            }
+            None => Ok(Step::Return(Some(Rc::new(Value::bool(!stop_when))))),
+        }
+    }
+
+    /// Evaluates the forms following the test of a `cond` or `case` clause which was selected
+    /// with `value`. A clause of the form `(test => f)` calls `f` with `value`, and a clause with
+    /// no forms evaluates to `value`.
+    fn eval_clause_body(
+        &mut self,
+        value: Rc<Value>,
+        body: &[Rc<Value>],
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        match body {
+            [] => Ok(Step::Return(Some(value))),
+            [arrow, function] if arrow.syntactic_name() == Some(clause::ARROW) => {
+                let function = self.eval_value(function, scope)?;
+                self.apply(&function, &list::from_rc_vec(vec![value]))
+            }
+            _ => self.eval_sequence(body, scope),
+        }
+    }
+
+    fn eval_cond(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::Cond,
+            form: form.clone(),
+        };
+        let clauses = list::to_vec(args).ok_or_else(malformed)?;
+        for (i, clause) in clauses.iter().enumerate() {
+            let (test, body) = match list::to_vec(&**clause).as_deref() {
+                Some([test, body @ ..]) => (test.clone(), body.to_vec()),
+                _ => return Err(malformed().into()),
+            };
+            if test.syntactic_name() == Some(clause::ELSE) {
+                if i != clauses.len() - 1 || body.is_empty() {
+                    return Err(malformed().into());
+                }
+                return self.eval_sequence(&body, scope);
+            }
+            let value = self.eval_value(&test, scope)?;
+            if value.is_true() {
+                return self.eval_clause_body(value, &body, scope);
+            }
         }
+        Ok(Step::Return(Some(Rc::new(Value::nil()))))
     }
 
-    fn call_lambda(&mut self, lambda: &Lambda, args: &Rc<Value>) -> Rc<Value> {
-        let args_vec = list::to_vec(args.clone());
-        if args_vec.len() != lambda.argument_names.len() {
-            panic!("incorrect number of arguments");
+    fn eval_case(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Step, RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::Case,
+            form: form.clone(),
+        };
+        let (key, clauses) = match list::to_vec(args).as_deref() {
+            Some([key, clauses @ ..]) => (key.clone(), clauses.to_vec()),
+            _ => return Err(malformed().into()),
+        };
+        let key = self.eval_value(&key, scope)?;
+        for (i, clause) in clauses.iter().enumerate() {
+            let (data, body) = match list::to_vec(&**clause).as_deref() {
+                Some([data, body @ ..]) if !body.is_empty() => (data.clone(), body.to_vec()),
+                _ => return Err(malformed().into()),
+            };
+            let selected = if data.syntactic_name() == Some(clause::ELSE) {
+                if i != clauses.len() - 1 {
+                    return Err(malformed().into());
+                }
+                true
+            } else {
+                list::to_vec(&*data)
+                    .ok_or_else(malformed)?
+                    .iter()
+                    .any(|datum| Value::is_eqv(datum, &key))
+            };
+            if selected {
+                return self.eval_clause_body(key, &body, scope);
+            }
         }
-        let arg_scope_index = self.make_scope();
-        for (name, value) in lambda.argument_names.iter().zip(args_vec.into_iter()) {
-            self.scopes[arg_scope_index].define_variable(name.as_str(), value);
+        Ok(Step::Return(Some(Rc::new(Value::nil()))))
+    }
+
+    fn eval_define_syntax(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<(), RuntimeError> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::DefineSyntax,
+            form: form.clone(),
+        };
+        let (name, rules) = match list::to_vec(args).as_deref() {
+            Some([name, rules]) => (name.clone(), rules.clone()),
+            _ => return Err(malformed().into()),
+        };
+        let symbol_name = name.as_symbol().ok_or_else(malformed)?;
+        let rules = self.eval_value(&rules, scope)?;
+        if !matches!(&*rules, Value::Function(Function::SyntaxRules(_))) {
+            return Err(RuntimeErrorKind::TypeMismatch {
+                expected: "syntax-rules",
+                found: rules,
+            }
+            .into());
         }
-        let scope_path = lambda.scope_path.push(arg_scope_index);
-        self.eval_with_scope_path(&lambda.code_ast, scope_path)
-            .unwrap()
+        scope.define_variable(symbol_name, rules);
+        Ok(())
+    }
+
+    fn eval_defmacro(
+        &mut self,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<(), RuntimeError> {
+        let (name, lambda) = match list::split_head(args) {
+            Some((name, lambda)) => (name, lambda),
+            None => {
+                return Err(RuntimeErrorKind::MalformedSpecialForm {
+                    keyword: Keyword::Defmacro,
+                    form: form.clone(),
+                }
+                .into())
+            }
+        };
+        let symbol_name =
+            name.as_symbol()
+                .ok_or_else(|| RuntimeErrorKind::MalformedSpecialForm {
+                    keyword: Keyword::Defmacro,
+                    form: form.clone(),
+                })?;
+        let lambda = self.define_lambda(Keyword::Defmacro, form, &lambda, scope)?;
+        scope.define_variable(
+            symbol_name,
+            Rc::new(Value::Function(Function::Macro(lambda))),
+        );
+        Ok(())
+    }
+
+    /// Calls the macro with the unevaluated arguments of a form, returning its expansion
+    fn expand_macro(
+        &mut self,
+        lambda: &Lambda,
+        args: &Rc<Value>,
+    ) -> Result<Rc<Value>, RuntimeError> {
+        let step = self.call_lambda(lambda, args)?;
+        self.run_step(step)?
+            .ok_or_else(|| RuntimeErrorKind::DefineInExpressionContext.into())
     }
 
-    fn eval_with_scope_path(
+    /// Expands `value` once if it is a form whose head names a macro, returning `None` otherwise
+    fn macroexpand_1(
         &mut self,
         value: &Rc<Value>,
-        scope_path: ScopePath,
-    ) -> Option<Rc<Value>> {
-        match &**value {
-            Value::Function(_) => Some(value.clone()),
-            Value::Atom(ref atom) => {
-                if let Some(symbol_name) = atom.symbol() {
-                    if let Some(built_in) = BuiltIn::from_str(symbol_name) {
-                        Some(Rc::new(Value::built_in(built_in)))
-                    } else if let Some(variable_value) =
-                        self.resolve_name(symbol_name, scope_path.clone())
-                    {
-                        Some(variable_value)
-                    } else {
-                        panic!("unhandled symbol: {}", symbol_name)
+        scope: &Rc<Scope>,
+    ) -> Result<Option<Rc<Value>>, RuntimeError> {
+        let (op, args) = match list::split_head(&**value) {
+            Some((op, args)) => (op, args),
+            None => return Ok(None),
+        };
+        if op.as_symbol().is_none() {
+            return Ok(None);
+        }
+        let macro_value = match self.resolve_name(&op, scope) {
+            Ok(macro_value) => macro_value,
+            Err(_) => return Ok(None),
+        };
+        match &*macro_value {
+            Value::Function(Function::Macro(lambda)) => self.expand_macro(lambda, &args).map(Some),
+            Value::Function(Function::SyntaxRules(rules)) => {
+                Ok(Some(rules.expand(value, &mut self.next_alias)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Evaluates `macroexpand` and `macroexpand-1` forms, which expand the form their argument
+    /// evaluates to repeatedly or once respectively
+    fn eval_macroexpand(
+        &mut self,
+        keyword: Keyword,
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Rc<Value>, RuntimeError> {
+        let mut value = match list::to_vec(args).as_deref() {
+            Some([value]) => self.eval_value(value, scope)?,
+            _ => {
+                return Err(RuntimeErrorKind::MalformedSpecialForm {
+                    keyword,
+                    form: form.clone(),
+                }
+                .into())
+            }
+        };
+        while let Some(expansion) = self.macroexpand_1(&value, scope)? {
+            value = expansion;
+            if keyword == Keyword::Macroexpand1 {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Builds the value of a quasiquoted template, evaluating the forms which are unquoted at
+    /// `depth` 1. Nested quasiquotes increase the depth and unquotes decrease it.
+    fn eval_quasiquote(
+        &mut self,
+        template: &Rc<Value>,
+        depth: usize,
+        scope: &Rc<Scope>,
+    ) -> Result<Rc<Value>, RuntimeError> {
+        let pair = match &**template {
+            Value::Pair(pair) => pair,
+            _ => return Ok(template.clone()),
+        };
+        if let Some((keyword, arg)) = prefix_form(template) {
+            let nested_depth = match keyword {
+                Keyword::Quasiquote => Some(depth + 1),
+                Keyword::Unquote if depth == 1 => return self.eval_value(&arg, scope),
+                Keyword::UnquoteSplicing if depth == 1 => {
+                    return Err(RuntimeErrorKind::MalformedSpecialForm {
+                        keyword,
+                        form: template.clone(),
                     }
-                } else {
-                    Some(value.clone())
+                    .into())
+                }
+                Keyword::Unquote | Keyword::UnquoteSplicing => Some(depth - 1),
+                _ => None,
+            };
+            if let Some(depth) = nested_depth {
+                let arg = self.eval_quasiquote(&arg, depth, scope)?;
+                return Ok(Rc::new(language::prefix_form(keyword, arg)));
+            }
+        }
+        let (car, cdr) = (pair.car(), pair.cdr());
+        let cdr = self.eval_quasiquote(&cdr, depth, scope)?;
+        match prefix_form(&car) {
+            Some((Keyword::UnquoteSplicing, arg)) if depth == 1 => {
+                let spliced = self.eval_value(&arg, scope)?;
+                let spliced =
+                    list::to_vec(spliced.clone()).ok_or(RuntimeErrorKind::NotAList(spliced))?;
+                Ok(list::from_rc_vec_with_end(spliced, cdr))
+            }
+            _ => {
+                let car = self.eval_quasiquote(&car, depth, scope)?;
+                Ok(Rc::new(Value::pair(car, cdr)))
+            }
+        }
+    }
+
+    /// Runs a step returned by `eval_step` to completion
+    fn run_step(&mut self, step: Step) -> Result<Option<Rc<Value>>, RuntimeError> {
+        match step {
+            Step::Return(result) => Ok(result),
+            Step::Eval(value, scope) => self.eval_in_scope(&value, &scope),
+        }
+    }
+
+    /// Calls `function` with a list of evaluated arguments, returning the body of a lambda to be
+    /// evaluated in tail position
+    fn apply(&mut self, function: &Rc<Value>, args: &Rc<Value>) -> Result<Step, RuntimeError> {
+        match &**function {
+            // handled here so that the function is called in tail position
+            Value::Function(Function::BuiltIn(BuiltIn::Apply)) => {
+                let (function, args) = built_in::spread_args(args)?;
+                self.apply(&function, &args)
+            }
+            Value::Function(Function::BuiltIn(built_in)) => built_in
+                .eval(self, args)
+                .map(|result| Step::Return(Some(result))),
+            Value::Function(Function::Lambda(lambda)) => self.call_lambda(lambda, args),
+            _ => Err(RuntimeErrorKind::NotCallable(function.clone()).into()),
+        }
+    }
+
+    /// Calls `function` with a list of evaluated arguments, running it to completion. This is how
+    /// built-ins call back into the evaluator.
+    pub fn call(
+        &mut self,
+        function: &Rc<Value>,
+        args: &Rc<Value>,
+    ) -> Result<Rc<Value>, RuntimeError> {
+        let step = self.apply(function, args)?;
+        self.run_step(step)?
+            .ok_or_else(|| RuntimeErrorKind::DefineInExpressionContext.into())
+    }
+
+    /// Evaluates forms in a loop so that forms in tail position (such as the branches of an `if`
+    /// and the bodies of lambdas) are evaluated without growing the stack
+    fn eval_in_scope(
+        &mut self,
+        value: &Rc<Value>,
+        scope: &Rc<Scope>,
+    ) -> Result<Option<Rc<Value>>, RuntimeError> {
+        let mut value = value.clone();
+        let mut scope = scope.clone();
+        loop {
+            let step = self.eval_step(&value, &scope).map_err(|mut error| {
+                if error.span.is_none() {
+                    error.span = self.source_map.get(&value).cloned();
+                }
+                error
+            })?;
+            match step {
+                Step::Return(result) => return Ok(result),
+                Step::Eval(next_value, next_scope) => {
+                    value = next_value;
+                    scope = next_scope;
                 }
             }
-            Value::Pair(Pair(op, args)) => {
-                if let Value::Atom(Atom::Symbol(symbol_name)) = &**op {
-                    if let Some(keyword) = Keyword::from_str(symbol_name.as_str()) {
+        }
+    }
+
+    fn eval_step(&mut self, value: &Rc<Value>, scope: &Rc<Scope>) -> Result<Step, RuntimeError> {
+        match &**value {
+            Value::Function(_) => Ok(Step::Return(Some(value.clone()))),
+            Value::Atom(Atom::Symbol(_)) | Value::Identifier(_) => self
+                .resolve_name(value, scope)
+                .map(|value| Step::Return(Some(value))),
+            Value::Atom(_) => Ok(Step::Return(Some(value.clone()))),
+            Value::Pair(pair) => {
+                let (op, args) = (&pair.car(), &pair.cdr());
+                if let Some(symbol_name) = op.syntactic_name() {
+                    if let Some(keyword) = Keyword::from_str(symbol_name) {
                         return match keyword {
                             Keyword::Define => {
-                                self.eval_define(&*args, scope_path.clone());
-                                None
+                                self.eval_define(value, args, scope)?;
+                                Ok(Step::Return(None))
                             }
-                            Keyword::Quote => Some(list::head(args)),
-                            Keyword::Lambda => Some(Rc::new(Value::Function(Function::Lambda(
-                                self.define_lambda(&*args, scope_path.clone()),
-                            )))),
-                            Keyword::If => {
-                                let (condition, if_true, if_false) = list::take3(args.clone());
-                                let condition_evalled = self
-                                    .eval_with_scope_path(&condition, scope_path.clone())
-                                    .unwrap();
-                                let condition_bool =
-                                    if let Value::Atom(Atom::Bool(condition_bool)) =
-                                        &*condition_evalled
-                                    {
-                                        *condition_bool
-                                    } else {
-                                        panic!()
-                                    };
-                                if condition_bool {
-                                    self.eval_with_scope_path(&if_true, scope_path.clone())
-                                } else {
-                                    self.eval_with_scope_path(&if_false, scope_path.clone())
+                            Keyword::Quote => match list::to_vec(&**args).as_deref() {
+                                Some([quoted]) => Ok(Step::Return(Some(quoted.clone()))),
+                                _ => Err(RuntimeErrorKind::MalformedSpecialForm {
+                                    keyword,
+                                    form: value.clone(),
                                 }
+                                .into()),
+                            },
+                            Keyword::Lambda => Ok(Step::Return(Some(Rc::new(Value::Function(
+                                Function::Lambda(self.define_lambda(keyword, value, args, scope)?),
+                            ))))),
+                            Keyword::If => self.eval_if(value, args, scope),
+                            Keyword::Begin => self.eval_begin(value, args, scope),
+                            Keyword::Cond => self.eval_cond(value, args, scope),
+                            Keyword::Case => self.eval_case(value, args, scope),
+                            Keyword::And | Keyword::Or => {
+                                self.eval_and_or(keyword, value, args, scope)
+                            }
+                            Keyword::When | Keyword::Unless => {
+                                self.eval_when(keyword, value, args, scope)
+                            }
+                            Keyword::Quasiquote => match list::to_vec(&**args).as_deref() {
+                                Some([template]) => self
+                                    .eval_quasiquote(template, 1, scope)
+                                    .map(|result| Step::Return(Some(result))),
+                                _ => Err(RuntimeErrorKind::MalformedSpecialForm {
+                                    keyword,
+                                    form: value.clone(),
+                                }
+                                .into()),
+                            },
+                            Keyword::Unquote | Keyword::UnquoteSplicing => {
+                                Err(RuntimeErrorKind::UnquoteOutsideQuasiquote(keyword).into())
+                            }
+                            Keyword::Defmacro => {
+                                self.eval_defmacro(value, args, scope)?;
+                                Ok(Step::Return(None))
                             }
+                            Keyword::SyntaxRules => {
+                                let rules = SyntaxRules::new(value, args, scope)?;
+                                Ok(Step::Return(Some(Rc::new(Value::Function(
+                                    Function::SyntaxRules(Rc::new(rules)),
+                                )))))
+                            }
+                            Keyword::DefineSyntax => {
+                                self.eval_define_syntax(value, args, scope)?;
+                                Ok(Step::Return(None))
+                            }
+                            Keyword::Macroexpand | Keyword::Macroexpand1 => self
+                                .eval_macroexpand(keyword, value, args, scope)
+                                .map(|result| Step::Return(Some(result))),
+                            Keyword::Set => {
+                                self.eval_set(value, args, scope)?;
+                                Ok(Step::Return(Some(Rc::new(Value::nil()))))
+                            }
+                            Keyword::Let
+                            | Keyword::LetStar
+                            | Keyword::Letrec
+                            | Keyword::LetrecStar => self.eval_let(keyword, value, args, scope),
                         };
                     };
                 }
-                let op_value = self.eval_with_scope_path(op, scope_path.clone()).unwrap();
+                let op_value = self.eval_value(op, scope)?;
                 match &*op_value {
-                    Value::Function(function) => {
-                        let args = list::map(args, |arg| {
-                            self.eval_with_scope_path(arg, scope_path.clone()).unwrap()
-                        });
-                        match function {
-                            Function::BuiltIn(built_in) => Some(built_in.eval(&args).unwrap()),
-                            Function::Lambda(lambda) => Some(self.call_lambda(lambda, &args)),
-                        }
+                    Value::Function(Function::Macro(lambda)) => {
+                        let expansion = self.expand_macro(lambda, args)?;
+                        return Ok(Step::Eval(expansion, scope.clone()));
+                    }
+                    Value::Function(Function::SyntaxRules(rules)) => {
+                        let expansion = rules.expand(value, &mut self.next_alias)?;
+                        return Ok(Step::Eval(expansion, scope.clone()));
                     }
-                    _ => panic!(
-                        "value in op position cannot be called in statement: {}",
-                        value
-                    ),
+                    Value::Function(_) => (),
+                    _ => return Err(RuntimeErrorKind::NotCallable(op_value).into()),
                 }
+                let args = list::to_vec(&**args)
+                    .ok_or_else(|| RuntimeErrorKind::NotAList(args.clone()))?
+                    .iter()
+                    .map(|arg| self.eval_value(arg, scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.apply(&op_value, &list::from_rc_vec(args))
             }
         }
     }
 
-    pub fn eval(&mut self, value: &Rc<Value>) -> Option<Rc<Value>> {
-        self.eval_with_scope_path(value, ScopePath::new())
+    /// Like `eval_in_scope` but treats a form which doesn't produce a value (ie. a
+    /// `define`) as an error.
+    fn eval_value(
+        &mut self,
+        value: &Rc<Value>,
+        scope: &Rc<Scope>,
+    ) -> Result<Rc<Value>, RuntimeError> {
+        self.eval_in_scope(value, scope)?
+            .ok_or_else(|| RuntimeErrorKind::DefineInExpressionContext.into())
+    }
+
+    pub fn eval(&mut self, value: &Rc<Value>) -> Result<Option<Rc<Value>>, RuntimeError> {
+        self.eval_in_scope(value, &self.global_scope.clone())
+    }
+
+    /// Parses and evaluates each value in `source` in turn, returning the result of the last one
+    pub fn eval_source(&mut self, source: &Rc<Source>) -> Result<Option<Rc<Value>>, Error> {
+        let values = self.parse_source(source)?;
+        let mut result = None;
+        for value in values {
+            result = self.eval(&value)?;
+        }
+        Ok(result)
     }
 
     pub fn get_top_level_variable(&self, name: &str) -> Option<Rc<Value>> {
-        self.scopes[0].variables_by_name.get(name).cloned()
+        self.global_scope.resolve_name(name).flatten()
+    }
+
+    pub fn top_level_variables(&self) -> Vec<(String, Rc<Value>)> {
+        self.global_scope
+            .variables_by_name
+            .borrow()
+            .iter()
+            .filter_map(|(name, value)| value.clone().map(|value| (name.clone(), value)))
+            .collect()
+    }
+
+    pub fn define_top_level_variable(&mut self, name: &str, value: Rc<Value>) {
+        self.global_scope.define_variable(name, value);
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Runtime;
-    use crate::{parse, value::Value};
+    use super::{Runtime, LIVE_SCOPE_COUNT};
+    use crate::{
+        error::{Arity, RuntimeErrorKind},
+        language::Keyword,
+        list, parse,
+        source::Source,
+        value::Value,
+    };
     use std::rc::Rc;
 
+    fn eval_result(string: &str) -> Rc<Value> {
+        let ast = parse::parse(string).unwrap();
+        let mut runtime = Runtime::new();
+        let mut result = None;
+        for v in ast {
+            result = runtime.eval(&Rc::new(v)).unwrap();
+        }
+        result.unwrap()
+    }
+
+    fn eval_error(string: &str) -> RuntimeErrorKind {
+        let ast = parse::parse(string).unwrap();
+        let mut runtime = Runtime::new();
+        for v in ast {
+            if let Err(error) = runtime.eval(&Rc::new(v)) {
+                return error.kind;
+            }
+        }
+        panic!("expected an error")
+    }
+
     #[test]
     fn simple_defines() {
         let string = r#"
@@ -257,7 +1294,7 @@ mod test {
         let ast = parse::parse(string).unwrap();
         let mut runtime = Runtime::new();
         for v in ast {
-            runtime.eval(&Rc::new(v));
+            runtime.eval(&Rc::new(v)).unwrap();
         }
         assert_eq!(
             *runtime.get_top_level_variable("my-string").unwrap(),
@@ -281,7 +1318,7 @@ mod test {
         let ast = parse::parse(string).unwrap();
         let mut runtime = Runtime::new();
         for v in ast {
-            runtime.eval(&Rc::new(v));
+            runtime.eval(&Rc::new(v)).unwrap();
         }
         assert_eq!(
             *runtime.get_top_level_variable("answer").unwrap(),
@@ -299,7 +1336,7 @@ mod test {
         let ast = parse::parse(string).unwrap();
         let mut runtime = Runtime::new();
         for v in ast {
-            runtime.eval(&Rc::new(v));
+            runtime.eval(&Rc::new(v)).unwrap();
         }
         assert_eq!(
             *runtime.get_top_level_variable("baz").unwrap(),
@@ -320,11 +1357,1489 @@ mod test {
         let ast = parse::parse(string).unwrap();
         let mut runtime = Runtime::new();
         for v in ast {
-            runtime.eval(&Rc::new(v));
+            runtime.eval(&Rc::new(v)).unwrap();
         }
         assert_eq!(
             *runtime.get_top_level_variable("result").unwrap(),
             Value::i64(120)
         );
     }
+
+    #[test]
+    fn define_in_lambda() {
+        let string = r#"
+            (define f (lambda (x) (define y (+ x 1))))
+            (f 1)
+        "#;
+        let ast = parse::parse(string).unwrap();
+        let mut runtime = Runtime::new();
+        for v in ast {
+            runtime.eval(&Rc::new(v)).unwrap();
+        }
+        // the definition is local to the call
+        assert_eq!(runtime.get_top_level_variable("y"), None);
+    }
+
+    #[test]
+    fn closures_capture_arguments() {
+        let string = r#"
+            (define make-adder (lambda (n) (lambda (x) (+ x n))))
+            (define add-2 (make-adder 2))
+            (define add-40 (make-adder 40))
+            (+ (add-2 0) (add-40 0))
+        "#;
+        assert_eq!(*eval_result(string), Value::i64(42));
+    }
+
+    #[test]
+    fn curried_closures() {
+        let string = r#"
+            ((((lambda (a) (lambda (b) (lambda (c) (* a (+ b c))))) 6) 3) 4)
+        "#;
+        assert_eq!(*eval_result(string), Value::i64(42));
+    }
+
+    #[test]
+    fn higher_order_closures() {
+        let string = r#"
+            (define compose (lambda (f g) (lambda (x) (f (g x)))))
+            (define twice (lambda (f) (compose f f)))
+            (define inc (lambda (x) (+ x 1)))
+            ((twice (twice inc)) 38)
+        "#;
+        assert_eq!(*eval_result(string), Value::i64(42));
+    }
+
+    #[test]
+    fn lexical_not_dynamic_scope() {
+        let string = r#"
+            (define x 'global)
+            (define get-x (lambda () x))
+            (define f (lambda (x) (get-x)))
+            (f 'local)
+        "#;
+        assert_eq!(*eval_result(string), Value::symbol("global"));
+    }
+
+    #[test]
+    fn shadowing() {
+        let string = r#"
+            (define x 1)
+            (define f (lambda (x) ((lambda (x) (* x 2)) (+ x 20))))
+            (f x)
+        "#;
+        assert_eq!(*eval_result(string), Value::i64(42));
+        let string = r#"
+            ((lambda (+) (+ 6 7)) *)
+        "#;
+        assert_eq!(*eval_result(string), Value::i64(42));
+    }
+
+    #[test]
+    fn counter() {
+        let string = r#"
+            (define make-counter (lambda ()
+                ((lambda (count)
+                    (lambda ()
+                        ((lambda (_) count) (set! count (+ count 1)))))
+                 0)))
+            (define c1 (make-counter))
+            (define c2 (make-counter))
+            (c1)
+            (c1)
+            (c2)
+            (define result (+ (* (c1) 10) (c2)))
+        "#;
+        let ast = parse::parse(string).unwrap();
+        let mut runtime = Runtime::new();
+        for v in ast {
+            runtime.eval(&Rc::new(v)).unwrap();
+        }
+        assert_eq!(
+            *runtime.get_top_level_variable("result").unwrap(),
+            Value::i64(32)
+        );
+    }
+
+    #[test]
+    fn set_updates_nearest_binding() {
+        let string = r#"
+            (define x 1)
+            (define f (lambda (x) (set! x 10)))
+            (f 2)
+            (define g (lambda () (set! x 42)))
+            (g)
+        "#;
+        let ast = parse::parse(string).unwrap();
+        let mut runtime = Runtime::new();
+        for v in ast {
+            runtime.eval(&Rc::new(v)).unwrap();
+        }
+        assert_eq!(
+            *runtime.get_top_level_variable("x").unwrap(),
+            Value::i64(42)
+        );
+    }
+
+    #[test]
+    fn mutable_pairs() {
+        let string = r#"
+            (define p '(1 2 3))
+            (define q p)
+            (set-car! p 'a)
+            (set-cdr! p '(b))
+            q
+        "#;
+        assert_eq!(
+            *eval_result(string),
+            list::from_vec(vec![Value::symbol("a"), Value::symbol("b")])
+        );
+    }
+
+    #[test]
+    fn circular_lists() {
+        let circular = "(define l (list 1 2)) (set-cdr! (cdr l) l)";
+        assert_eq!(
+            *eval_result(&format!("{} (list? l)", circular)),
+            Value::bool(false)
+        );
+        assert!(matches!(
+            eval_error(&format!("{} (length l)", circular)),
+            RuntimeErrorKind::NotAList(_)
+        ));
+        assert!(matches!(
+            eval_error(&format!("{} (apply + l)", circular)),
+            RuntimeErrorKind::NotAList(_)
+        ));
+        assert_eq!(
+            eval_result(&format!("{} l", circular)).to_string(),
+            "(1 2 1 2 ...)"
+        );
+        // a cycle which doesn't include the start of the list
+        let lasso = "(define l (list 1 2 3)) (set-cdr! (cddr l) (cdr l))";
+        assert_eq!(
+            *eval_result(&format!("{} (list? l)", lasso)),
+            Value::bool(false)
+        );
+        assert!(matches!(
+            eval_error(&format!("{} (length l)", lasso)),
+            RuntimeErrorKind::NotAList(_)
+        ));
+        assert_eq!(
+            *eval_result("(define l (list 1 2 3)) (set-cdr! (cddr l) '(4)) (length l)"),
+            Value::i64(4)
+        );
+    }
+
+    #[test]
+    fn tail_calls() {
+        let string = r#"
+            (define count-down (lambda (n)
+                (if (= n 0)
+                    'done
+                    (count-down (- n 1)))))
+            (count-down 100000)
+        "#;
+        assert_eq!(*eval_result(string), Value::symbol("done"));
+        let string = r#"
+            (define even? (lambda (n)
+                (if (= n 0) true (odd? (- n 1)))))
+            (define odd? (lambda (n)
+                (if (= n 0) false (even? (- n 1)))))
+            (even? 100001)
+        "#;
+        assert_eq!(*eval_result(string), Value::bool(false));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            eval_error("(+ 1 foo)"),
+            RuntimeErrorKind::UnboundVariable("foo".to_string())
+        );
+        assert_eq!(
+            eval_error("((lambda (x y) x) 1)"),
+            RuntimeErrorKind::ArityMismatch {
+                expected: Arity::exactly(2),
+                found: 1
+            }
+        );
+        assert_eq!(
+            eval_error("(1 2)"),
+            RuntimeErrorKind::NotCallable(Rc::new(Value::i64(1)))
+        );
+        assert!(matches!(
+            eval_error("(lambda (1) 1)"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::Lambda,
+                ..
+            }
+        ));
+        assert!(matches!(
+            eval_error("(if true 1 2 3)"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::If,
+                ..
+            }
+        ));
+        assert_eq!(
+            eval_error("(set! x 2)"),
+            RuntimeErrorKind::UnboundVariable("x".to_string())
+        );
+        assert_eq!(
+            eval_error("(set-car! '() 2)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "pair",
+                found: Rc::new(Value::nil()),
+            }
+        );
+        assert_eq!(
+            eval_error("(+ 1 (define x 2))"),
+            RuntimeErrorKind::DefineInExpressionContext
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let source = Source::new(
+            "test.lisp",
+            r#"(define f (lambda (x)
+  (+ x y)))
+(f 1)
+"#,
+        );
+        let mut runtime = Runtime::new();
+        let ast = runtime.parse_source(&source).unwrap();
+        runtime.eval(&ast[0]).unwrap();
+        let error = runtime.eval(&ast[1]).unwrap_err();
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::UnboundVariable("y".to_string())
+        );
+        assert_eq!(
+            format!("{}", error),
+            "test.lisp:2:8: unbound variable: y\n  |\n2 |   (+ x y)))\n  |        ^"
+        );
+    }
+
+    #[test]
+    fn call_scopes_are_freed() {
+        fn eval(runtime: &mut Runtime, string: &str) -> Option<Rc<Value>> {
+            let mut result = None;
+            for v in parse::parse(string).unwrap() {
+                result = runtime.eval(&Rc::new(v)).unwrap();
+            }
+            result
+        }
+        let live_scopes = || LIVE_SCOPE_COUNT.with(|count| count.get());
+        let mut runtime = Runtime::new();
+        eval(
+            &mut runtime,
+            r#"
+            (define count-down (lambda (n)
+                (if (= n 0)
+                    'done
+                    (count-down (- n 1)))))
+            (define make-adder (lambda (n) (lambda (x) (+ x n))))
+            (define repeat (lambda (f n)
+                (if (= n 0)
+                    'done
+                    (begin (f n) (repeat f (- n 1))))))
+            (define with-helper (lambda (n)
+                (define helper (lambda () n))
+                (helper)))
+            (define with-letrec (lambda (n)
+                (letrec ((helper (lambda () n)))
+                    (helper))))
+        "#,
+        );
+        let (live_before, _) = live_scopes();
+        LIVE_SCOPE_COUNT.with(|count| count.set((live_before, live_before)));
+        eval(&mut runtime, "(count-down 100000)");
+        let (live_after, peak) = live_scopes();
+        assert_eq!(live_after, live_before);
+        assert!(peak <= live_before + 2);
+
+        // the scope of a call is kept alive by a closure which captures it
+        let add_2 = eval(&mut runtime, "(make-adder 2)").unwrap();
+        assert_eq!(live_scopes().0, live_before + 1);
+        drop(add_2);
+        assert_eq!(live_scopes().0, live_before);
+
+        // helpers defined inside a call are stored in the scope they capture, so the scopes are
+        // only freed by collecting cycles, which happens periodically as scopes are created
+        for function in ["with-helper", "with-letrec"] {
+            LIVE_SCOPE_COUNT.with(|count| count.set((live_before, live_before)));
+            eval(&mut runtime, &format!("(repeat {} 100000)", function));
+            let (_, peak) = live_scopes();
+            assert!(
+                peak <= live_before + 4 * Runtime::MIN_COLLECT_AT,
+                "{}: {} scopes",
+                function,
+                peak
+            );
+            runtime.collect_cycles();
+            assert_eq!(live_scopes().0, live_before, "{}", function);
+        }
+    }
+
+    #[test]
+    fn let_forms() {
+        assert_eq!(
+            *eval_result("(define x 1) (let ((x 2) (y x)) (+ x y))"),
+            Value::i64(3)
+        );
+        assert_eq!(
+            *eval_result("(define x 1) (let* ((x 2) (y x)) (+ x y))"),
+            Value::i64(4)
+        );
+        assert_eq!(
+            *eval_result(
+                r#"
+            (letrec ((even? (lambda (n) (if (= n 0) true (odd? (- n 1)))))
+                     (odd? (lambda (n) (if (= n 0) false (even? (- n 1))))))
+                (even? 100))
+        "#
+            ),
+            Value::bool(true)
+        );
+        assert_eq!(
+            *eval_result("(letrec* ((x 2) (y (+ x 1))) (* x y))"),
+            Value::i64(6)
+        );
+        assert_eq!(
+            eval_error("(letrec ((x 2) (y (+ x 1))) y)"),
+            RuntimeErrorKind::UninitializedVariable("x".to_string())
+        );
+        assert!(matches!(
+            eval_error("(let ((x)) x)"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::Let,
+                ..
+            }
+        ));
+        assert!(matches!(
+            eval_error("(let* x x)"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::LetStar,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn named_let() {
+        assert_eq!(
+            *eval_result(
+                r#"
+            (let loop ((i 0) (acc 0))
+                (if (= i 100000)
+                    acc
+                    (loop (+ i 1) (+ acc 2))))
+        "#
+            ),
+            Value::i64(200000)
+        );
+        let mut runtime = Runtime::new();
+        let (live_before, _) = LIVE_SCOPE_COUNT.with(|count| count.get());
+        LIVE_SCOPE_COUNT.with(|count| count.set((live_before, live_before)));
+        for v in parse::parse("(let loop ((i 0)) (if (= i 100000) i (loop (+ i 1))))").unwrap() {
+            runtime.eval(&Rc::new(v)).unwrap();
+        }
+        let (live_after, peak) = LIVE_SCOPE_COUNT.with(|count| count.get());
+        assert_eq!(live_after, live_before);
+        assert!(peak <= live_before + 2);
+    }
+
+    #[test]
+    fn sequencing() {
+        assert_eq!(
+            *eval_result("(begin (define x 1) (set! x (+ x 1)) x)"),
+            Value::i64(2)
+        );
+        assert_eq!(*eval_result("(begin)"), Value::nil());
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define counter 0)
+            (define bump (lambda (n)
+                (set! counter (+ counter n))
+                (set! counter (+ counter n))
+                counter))
+            (bump 3)
+        "#
+            ),
+            Value::i64(6)
+        );
+        assert_eq!(
+            *eval_result("(let ((x 1)) (define y (+ x 1)) (* x y))"),
+            Value::i64(2)
+        );
+        assert!(matches!(
+            eval_error("(lambda (x))"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::Lambda,
+                ..
+            }
+        ));
+        assert!(matches!(
+            eval_error("(begin . 1)"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::Begin,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn internal_defines() {
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define f (lambda (n)
+                (define even? (lambda (n) (if (= n 0) true (odd? (- n 1)))))
+                (define odd? (lambda (n) (if (= n 0) false (even? (- n 1)))))
+                (even? n)))
+            (f 10)
+        "#
+            ),
+            Value::bool(true)
+        );
+        // internal defines shadow outer variables from the start of the body
+        assert_eq!(
+            eval_error(
+                r#"
+            (define x 1)
+            ((lambda ()
+                (define y x)
+                (define x 2)
+                y))
+        "#
+            ),
+            RuntimeErrorKind::UninitializedVariable("x".to_string())
+        );
+    }
+
+    #[test]
+    fn conditionals() {
+        assert_eq!(*eval_result("(if 0 1 2)"), Value::i64(1));
+        assert_eq!(*eval_result("(if '() 1 2)"), Value::i64(1));
+        assert_eq!(*eval_result("(if false 1)"), Value::nil());
+        assert_eq!(*eval_result("(when (= 1 1) 1 2)"), Value::i64(2));
+        assert_eq!(*eval_result("(unless (= 1 1) 1 2)"), Value::nil());
+        let sign = r#"
+            (define sign (lambda (n)
+                (cond ((= n 0) 'zero)
+                      ((= n (* n n)) 'one)
+                      (else 'other))))
+        "#;
+        assert_eq!(
+            *eval_result(&format!("{} (sign 0)", sign)),
+            Value::symbol("zero")
+        );
+        assert_eq!(
+            *eval_result(&format!("{} (sign 1)", sign)),
+            Value::symbol("one")
+        );
+        assert_eq!(
+            *eval_result(&format!("{} (sign 5)", sign)),
+            Value::symbol("other")
+        );
+        assert_eq!(*eval_result("(cond (false 1))"), Value::nil());
+        assert_eq!(*eval_result("(cond (2))"), Value::i64(2));
+        assert_eq!(
+            *eval_result("(cond ((+ 1 2) => (lambda (x) (* x x))))"),
+            Value::i64(9)
+        );
+        assert!(matches!(
+            eval_error("(cond (else 1) (true 2))"),
+            RuntimeErrorKind::MalformedSpecialForm {
+                keyword: Keyword::Cond,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn case() {
+        let classify = r#"
+            (define classify (lambda (x)
+                (case x
+                    ((1 2 3) 'small)
+                    ((a b) 'letter)
+                    (else 'unknown))))
+        "#;
+        assert_eq!(
+            *eval_result(&format!("{} (classify 2)", classify)),
+            Value::symbol("small")
+        );
+        assert_eq!(
+            *eval_result(&format!("{} (classify 'b)", classify)),
+            Value::symbol("letter")
+        );
+        assert_eq!(
+            *eval_result(&format!("{} (classify \"a\")", classify)),
+            Value::symbol("unknown")
+        );
+        assert_eq!(*eval_result("(case 1 ((2) 'two))"), Value::nil());
+        assert_eq!(
+            *eval_result("(case 3 (else => (lambda (x) (+ x 1))))"),
+            Value::i64(4)
+        );
+    }
+
+    #[test]
+    fn and_or() {
+        assert_eq!(*eval_result("(and)"), Value::bool(true));
+        assert_eq!(*eval_result("(or)"), Value::bool(false));
+        assert_eq!(*eval_result("(and 1 2 3)"), Value::i64(3));
+        assert_eq!(*eval_result("(and 1 false 3)"), Value::bool(false));
+        assert_eq!(*eval_result("(or false 2 3)"), Value::i64(2));
+        // evaluation stops at the first operand which decides the result
+        assert_eq!(*eval_result("(and false undefined)"), Value::bool(false));
+        assert_eq!(*eval_result("(or 1 undefined)"), Value::i64(1));
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define loop (lambda (n) (or (= n 0) (loop (- n 1)))))
+            (loop 100000)
+        "#
+            ),
+            Value::bool(true)
+        );
+    }
+
+    #[test]
+    fn quasiquote() {
+        assert_eq!(
+            eval_result("(define x 1) (define xs '(2 3)) `(a ,x ,@xs b)").to_string(),
+            "(a 1 2 3 b)"
+        );
+        assert_eq!(
+            eval_result("(define xs '(2 3)) `(1 ,@xs)").to_string(),
+            "(1 2 3)"
+        );
+        assert_eq!(eval_result("(define x 1) `(a . ,x)").to_string(), "(a . 1)");
+        assert_eq!(
+            eval_result("(define xs '(1 2)) `(begin ,@xs)").to_string(),
+            "(begin 1 2)"
+        );
+        // only the outermost level of nested quasiquotes is evaluated
+        assert_eq!(
+            eval_result("(define x 1) `(a `(b ,(c ,x)))").to_string(),
+            "(a (quasiquote (b (unquote (c 1)))))"
+        );
+        assert_eq!(
+            eval_error("(define x 1) ,x"),
+            RuntimeErrorKind::UnquoteOutsideQuasiquote(Keyword::Unquote)
+        );
+        assert_eq!(
+            eval_error("(define x 1) `(a ,@x)"),
+            RuntimeErrorKind::NotAList(Rc::new(Value::i64(1)))
+        );
+    }
+
+    #[test]
+    fn macros() {
+        let swap = r#"
+            (defmacro swap! (a b)
+                `(let ((tmp ,a))
+                    (set! ,a ,b)
+                    (set! ,b tmp)))
+        "#;
+        assert_eq!(
+            eval_result(&format!(
+                "{} (define x 1) (define y 2) (swap! x y) `(,x . ,y)",
+                swap
+            ))
+            .to_string(),
+            "(2 . 1)"
+        );
+        // arguments are passed to the macro without being evaluated
+        assert_eq!(
+            *eval_result(
+                r#"
+            (defmacro my-if (c t f) `(cond (,c ,t) (else ,f)))
+            (my-if true 1 undefined)
+        "#
+            ),
+            Value::i64(1)
+        );
+        assert_eq!(
+            eval_result(&format!("{} (macroexpand-1 '(swap! x y))", swap)).to_string(),
+            "(let ((tmp x)) (set! x y) (set! y tmp))"
+        );
+        assert_eq!(
+            eval_result(
+                r#"
+            (defmacro inc! (x) `(add! ,x 1))
+            (defmacro add! (x n) `(set! ,x (+ ,x ,n)))
+            (macroexpand '(inc! y))
+        "#
+            )
+            .to_string(),
+            "(set! y (+ y 1))"
+        );
+        assert_eq!(eval_result("(macroexpand '(+ 1 2))").to_string(), "(+ 1 2)");
+        // a macro expanding to a recursive call in tail position doesn't grow the stack
+        assert_eq!(
+            *eval_result(
+                r#"
+            (defmacro unless-zero (n body) `(if (= ,n 0) 'done ,body))
+            (define count-down (lambda (n) (unless-zero n (count-down (- n 1)))))
+            (count-down 100000)
+        "#
+            ),
+            Value::symbol("done")
+        );
+    }
+
+    #[test]
+    fn syntax_rules() {
+        let my_or = r#"
+            (define-syntax my-or
+                (syntax-rules ()
+                    ((_) false)
+                    ((_ e) e)
+                    ((_ e r ...) (let ((t e)) (if t t (my-or r ...))))))
+        "#;
+        assert_eq!(
+            *eval_result(&format!("{} (my-or false false 3)", my_or)),
+            Value::i64(3)
+        );
+        assert_eq!(
+            *eval_result(&format!("{} (my-or)", my_or)),
+            Value::bool(false)
+        );
+        // the `t` bound by the macro doesn't capture the user's `t`
+        assert_eq!(
+            *eval_result(&format!("{} (define t 5) (my-or false t)", my_or)),
+            Value::i64(5)
+        );
+        let swap = r#"
+            (define-syntax swap!
+                (syntax-rules ()
+                    ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))
+        "#;
+        assert_eq!(
+            eval_result(&format!(
+                "{} (define tmp 1) (define other 2) (swap! tmp other) `(,tmp ,other)",
+                swap
+            ))
+            .to_string(),
+            "(2 1)"
+        );
+        assert_eq!(
+            eval_result(&format!("{} (macroexpand '(swap! x y))", swap)).to_string(),
+            "(let ((tmp x)) (set! x y) (set! y tmp))"
+        );
+        assert_eq!(
+            eval_error("(define-syntax two (syntax-rules () ((_ a b) a))) (two 1)"),
+            RuntimeErrorKind::NoMatchingSyntaxRule(Rc::new(list::from_vec(vec![
+                Value::symbol("two"),
+                Value::i64(1)
+            ])))
+        );
+    }
+
+    #[test]
+    fn syntax_rules_referential_transparency() {
+        // free identifiers in a template refer to the bindings where the macro was defined,
+        // rather than where it is used
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define helper (lambda (x) (* x 2)))
+            (define-syntax double (syntax-rules () ((_ e) (helper e))))
+            (let ((helper (lambda (x) 0))) (double 5))
+        "#
+            ),
+            Value::i64(10)
+        );
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define-syntax add (syntax-rules () ((_ a b) (+ a b))))
+            (let ((+ -)) (add 1 2))
+        "#
+            ),
+            Value::i64(3)
+        );
+        // set! on a free identifier updates the variable where the macro was defined
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define count 0)
+            (define-syntax bump! (syntax-rules () ((_) (set! count (+ count 1)))))
+            (let ((count 10)) (bump!) (bump!))
+            count
+        "#
+            ),
+            Value::i64(2)
+        );
+    }
+
+    #[test]
+    fn syntax_rules_data() {
+        // symbols in quoted data, quasiquote templates and case clauses are not renamed
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define-syntax m
+                (syntax-rules () ((_ x) (case x ((a) 'matched) (else 'nope)))))
+            (m 'a)
+        "#
+            ),
+            Value::symbol("matched")
+        );
+        let quasiquote = "(define-syntax m (syntax-rules () ((_ x) `(a ,x (b ,@(list x))))))";
+        assert_eq!(
+            *eval_result(&format!("{} (equal? (m 1) '(a 1 (b 1)))", quasiquote)),
+            Value::bool(true)
+        );
+        assert_eq!(
+            *eval_result(&format!("{} (eq? (car (m 1)) 'a)", quasiquote)),
+            Value::bool(true)
+        );
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define-syntax m (syntax-rules () ((_) (let ((a 'a)) (list a 'b)))))
+            (equal? (m) '(a b))
+        "#
+            ),
+            Value::bool(true)
+        );
+    }
+
+    #[test]
+    fn syntax_rules_patterns() {
+        let my_let = r#"
+            (define-syntax my-let*
+                (syntax-rules ()
+                    ((_ () body ...) (let () body ...))
+                    ((_ ((x v) rest ...) body ...)
+                        (let ((x v)) (my-let* (rest ...) body ...)))))
+        "#;
+        assert_eq!(
+            *eval_result(&format!("{} (my-let* ((a 1) (b (+ a 1))) (* a b))", my_let)),
+            Value::i64(2)
+        );
+        let for_each = r#"
+            (define-syntax sum-for
+                (syntax-rules (in)
+                    ((_ x in (v ...) body) (+ (let ((x v)) body) ...))))
+        "#;
+        assert_eq!(
+            *eval_result(&format!("{} (sum-for y in (2 3) (* y y))", for_each)),
+            Value::i64(13)
+        );
+        assert!(matches!(
+            eval_error(&format!("{} (sum-for y on (1 2 3) y)", for_each)),
+            RuntimeErrorKind::NoMatchingSyntaxRule(_)
+        ));
+        // nested ellipses and patterns following an ellipsis
+        assert_eq!(
+            eval_result(
+                r#"
+            (define-syntax flatten
+                (syntax-rules () ((_ (x ...) ... last) '(x ... ... last))))
+            (flatten (1 2) (3) () 4)
+        "#
+            )
+            .to_string(),
+            "(1 2 3 4)"
+        );
+        assert_eq!(
+            eval_result(
+                r#"
+            (define-syntax ellipsis
+                (syntax-rules () ((_ x) '(x (... ...)))))
+            (ellipsis 1)
+        "#
+            )
+            .to_string(),
+            "(1 ...)"
+        );
+    }
+
+    #[test]
+    fn variadic_lambdas() {
+        assert_eq!(
+            eval_result("((lambda (a b . rest) `(,a ,b ,rest)) 1 2 3 4)").to_string(),
+            "(1 2 (3 4))"
+        );
+        assert_eq!(
+            eval_result("((lambda (a . rest) rest) 1)").to_string(),
+            "()"
+        );
+        assert_eq!(
+            eval_result("((lambda args args) 1 2 3)").to_string(),
+            "(1 2 3)"
+        );
+        assert_eq!(eval_result("((lambda args args))").to_string(), "()");
+        assert_eq!(
+            eval_error("((lambda (a b . rest) a) 1)"),
+            RuntimeErrorKind::ArityMismatch {
+                expected: Arity::at_least(2),
+                found: 1
+            }
+        );
+        assert_eq!(
+            *eval_result(
+                r#"
+            (defmacro my-when (condition . body) `(if ,condition (begin ,@body) nil))
+            (my-when true 1 2 3)
+        "#
+            ),
+            Value::i64(3)
+        );
+    }
+
+    #[test]
+    fn optional_parameters() {
+        let f = "(define f (lambda (a #!optional b (c (+ a 10)) . rest) `(,a ,b ,c ,rest)))";
+        assert_eq!(
+            eval_result(&format!("{} (f 1)", f)).to_string(),
+            "(1 () 11 ())"
+        );
+        assert_eq!(
+            eval_result(&format!("{} (f 1 2)", f)).to_string(),
+            "(1 2 11 ())"
+        );
+        assert_eq!(
+            eval_result(&format!("{} (f 1 2 3 4 5)", f)).to_string(),
+            "(1 2 3 (4 5))"
+        );
+        assert_eq!(
+            eval_error("((lambda (a #!optional b) a))"),
+            RuntimeErrorKind::ArityMismatch {
+                expected: Arity {
+                    min: 1,
+                    max: Some(2)
+                },
+                found: 0
+            }
+        );
+        assert_eq!(
+            eval_error("((lambda (a #!optional b) a) 1 2 3)").to_string(),
+            "expected 1 to 2 arguments, found 3"
+        );
+        for malformed in [
+            "(lambda (a 1) a)",
+            "(lambda (a . 1) a)",
+            "(lambda 1 1)",
+            "(lambda (#!optional (b)) b)",
+        ] {
+            assert!(
+                matches!(
+                    eval_error(malformed),
+                    RuntimeErrorKind::MalformedSpecialForm {
+                        keyword: Keyword::Lambda,
+                        ..
+                    }
+                ),
+                "{}",
+                malformed
+            );
+        }
+    }
+
+    #[test]
+    fn variadic_arithmetic() {
+        for (expression, expected) in [
+            ("(+)", 0),
+            ("(+ 1 2 3 4)", 10),
+            ("(*)", 1),
+            ("(* 2 3 4)", 24),
+            ("(- 5)", -5),
+            ("(- 10 1 2 3)", 4),
+            ("(/ 100 5 2)", 10),
+            ("(/ -8 2)", -4),
+            ("(quotient 7 -2)", -3),
+            ("(remainder -7 2)", -1),
+            ("(modulo -7 2)", 1),
+            ("(modulo 7 -2)", -1),
+            ("(abs -4)", 4),
+            ("(min 3 1 2)", 1),
+            ("(max 3 1 2)", 3),
+        ] {
+            assert_eq!(
+                *eval_result(expression),
+                Value::i64(expected),
+                "{}",
+                expression
+            );
+        }
+        for (expression, expected) in [
+            ("(< 1 2 3)", true),
+            ("(< 1 3 2)", false),
+            ("(<= 1 1 2)", true),
+            ("(> 3 2 1)", true),
+            ("(>= 3 3 4)", false),
+            ("(= 2 2 2)", true),
+            ("(= 2 2 3)", false),
+            ("(< 1)", true),
+        ] {
+            assert_eq!(
+                *eval_result(expression),
+                Value::bool(expected),
+                "{}",
+                expression
+            );
+        }
+        assert_eq!(eval_error("(/ 1 0)"), RuntimeErrorKind::DivisionByZero);
+        assert_eq!(eval_error("(modulo 1 0)"), RuntimeErrorKind::DivisionByZero);
+        assert_eq!(
+            eval_error("(-)"),
+            RuntimeErrorKind::ArityMismatch {
+                expected: Arity::at_least(1),
+                found: 0
+            }
+        );
+        assert_eq!(
+            eval_error("(< 1 'a 2)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "number",
+                found: Rc::new(Value::symbol("a")),
+            }
+        );
+    }
+
+    #[test]
+    fn big_integers() {
+        assert_eq!(
+            eval_result(
+                r#"
+            (define factorial (lambda (n)
+                (if (= n 0)
+                    1
+                    (* n (factorial (- n 1))))))
+            (factorial 30)
+        "#
+            )
+            .to_string(),
+            "265252859812191058636308480000000"
+        );
+        assert_eq!(
+            eval_result("(+ 9223372036854775807 1)").to_string(),
+            "9223372036854775808"
+        );
+        assert_eq!(
+            *eval_result("(- 9223372036854775808 1)"),
+            Value::i64(i64::MAX)
+        );
+        assert_eq!(
+            eval_result("(- -9223372036854775807 2)").to_string(),
+            "-9223372036854775809"
+        );
+        assert_eq!(
+            eval_result("(quotient 100000000000000000000 3)").to_string(),
+            "33333333333333333333"
+        );
+        assert_eq!(
+            *eval_result("(< 9223372036854775807 100000000000000000000)"),
+            Value::bool(true)
+        );
+        assert_eq!(
+            *eval_result("(= 100000000000000000000 100000000000000000000)"),
+            Value::bool(true)
+        );
+    }
+
+    #[test]
+    fn floats() {
+        let eval_string = |string| eval_result(string).to_string();
+        assert_eq!(eval_string("(+ 1 2.5)"), "3.5");
+        assert_eq!(eval_string("(* 2 0.5)"), "1.0");
+        assert_eq!(eval_string("(/ 7 2)"), "7/2");
+        assert_eq!(eval_string("(/ 7 2.0)"), "3.5");
+        assert_eq!(eval_string("(/ 1.0 0.0)"), "+inf.0");
+        assert_eq!(eval_string("(/ 0.0 0.0)"), "+nan.0");
+        assert_eq!(eval_string("(max 1 2.0 3)"), "3.0");
+        assert_eq!(eval_string("(min 1 2 3)"), "1");
+        assert_eq!(*eval_result("(= 1 1.0)"), Value::bool(true));
+        assert_eq!(*eval_result("(< 1 1.5 2)"), Value::bool(true));
+        assert_eq!(eval_string("(sqrt 16)"), "4");
+        assert_eq!(eval_string("(sqrt 2.25)"), "1.5");
+        assert_eq!(
+            eval_string("(expt 2 100)"),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(eval_string("(expt 2 -1)"), "1/2");
+        assert_eq!(eval_string("(expt 2.0 3)"), "8.0");
+        // exponents too large to compute exactly are an error, unless the result is trivial
+        assert_eq!(
+            eval_error("(expt 2 2147483648)"),
+            RuntimeErrorKind::ExponentTooLarge
+        );
+        assert_eq!(
+            eval_error("(expt 2 100000000000000000000)"),
+            RuntimeErrorKind::ExponentTooLarge
+        );
+        assert_eq!(eval_string("(expt 1 100000000000000000000)"), "1");
+        assert_eq!(eval_string("(expt -1 100000000000000000001)"), "-1");
+        assert_eq!(eval_string("(expt -1 -2147483648000)"), "1");
+        assert_eq!(eval_string("(expt 0 2147483648)"), "0");
+        assert_eq!(eval_string("(expt 2.0 2147483648)"), "+inf.0");
+        assert_eq!(eval_string("(exp 0)"), "1.0");
+        assert_eq!(eval_string("(log 1)"), "0.0");
+        assert_eq!(eval_string("(log 8 2)"), "3.0");
+        assert_eq!(eval_string("(sin 0)"), "0.0");
+        assert_eq!(eval_string("(cos 0)"), "1.0");
+        assert_eq!(
+            eval_string("(atan 1 1)"),
+            (std::f64::consts::PI / 4.0).to_string()
+        );
+        assert_eq!(eval_string("(floor -2.5)"), "-3.0");
+        assert_eq!(eval_string("(ceiling 2.1)"), "3.0");
+        assert_eq!(eval_string("(round 2.5)"), "2.0");
+        assert_eq!(eval_string("(round 3.5)"), "4.0");
+        assert_eq!(eval_string("(truncate -2.7)"), "-2.0");
+        assert_eq!(eval_string("(floor 5)"), "5");
+        assert_eq!(eval_string("(exact->inexact 1)"), "1.0");
+        assert_eq!(eval_error("(/ 1 0)"), RuntimeErrorKind::DivisionByZero);
+        assert_eq!(
+            eval_error("(quotient 1.0 0.0)"),
+            RuntimeErrorKind::DivisionByZero
+        );
+    }
+
+    #[test]
+    fn rationals() {
+        let eval_string = |string| eval_result(string).to_string();
+        assert_eq!(eval_string("(/ 1 3)"), "1/3");
+        assert_eq!(eval_string("(/ 6 -4)"), "-3/2");
+        assert_eq!(eval_string("(/ 2)"), "1/2");
+        assert_eq!(eval_string("(+ 1/3 2/3)"), "1");
+        assert_eq!(eval_string("(* 2/3 3/4)"), "1/2");
+        assert_eq!(eval_string("(- 1/2 1)"), "-1/2");
+        assert_eq!(eval_string("(+ 1/2 0.25)"), "0.75");
+        assert_eq!(
+            eval_string("(/ 1 100000000000000000000)"),
+            "1/100000000000000000000"
+        );
+        assert_eq!(eval_string("(numerator 6/4)"), "3");
+        assert_eq!(eval_string("(denominator 6/4)"), "2");
+        assert_eq!(eval_string("(denominator 5)"), "1");
+        assert_eq!(eval_string("(denominator 0.75)"), "4.0");
+        assert_eq!(eval_string("(expt 2/3 2)"), "4/9");
+        assert_eq!(eval_string("(sqrt 9/4)"), "3/2");
+        assert_eq!(eval_string("(floor -7/2)"), "-4");
+        assert_eq!(eval_string("(ceiling -7/2)"), "-3");
+        assert_eq!(eval_string("(round 5/2)"), "2");
+        assert_eq!(eval_string("(round 7/2)"), "4");
+        assert_eq!(eval_string("(truncate -7/2)"), "-3");
+        assert_eq!(eval_string("(abs -1/2)"), "1/2");
+        assert_eq!(eval_string("(max 1/2 1/3)"), "1/2");
+        assert_eq!(eval_string("(exact->inexact 1/4)"), "0.25");
+        assert_eq!(*eval_result("(< 1/3 0.34 1/2)"), Value::bool(true));
+        assert_eq!(*eval_result("(= 1/2 0.5 2/4)"), Value::bool(true));
+        assert_eq!(eval_error("(/ 1/2 0)"), RuntimeErrorKind::DivisionByZero);
+    }
+
+    #[test]
+    fn lists() {
+        let eval_string = |string| eval_result(string).to_string();
+        assert_eq!(eval_string("(car '(1 2 3))"), "1");
+        assert_eq!(eval_string("(cdr '(1 2 3))"), "(2 3)");
+        assert_eq!(eval_string("(cons 1 '(2))"), "(1 2)");
+        assert_eq!(eval_string("(cons 1 2)"), "(1 . 2)");
+        assert_eq!(eval_string("(list 1 (+ 1 1) 'three)"), "(1 2 three)");
+        assert_eq!(eval_string("(list)"), "()");
+        assert_eq!(eval_string("(length '(1 2 3))"), "3");
+        assert_eq!(eval_string("(length nil)"), "0");
+        assert_eq!(
+            eval_string("(append '(1 2) '(3) nil '(4 5))"),
+            "(1 2 3 4 5)"
+        );
+        assert_eq!(eval_string("(append '(1) 2)"), "(1 . 2)");
+        assert_eq!(eval_string("(append)"), "()");
+        assert_eq!(eval_string("(reverse '(1 2 3))"), "(3 2 1)");
+        assert_eq!(eval_string("(list-ref '(a b c) 2)"), "c");
+        assert_eq!(eval_string("(list-tail '(a b c) 1)"), "(b c)");
+        assert_eq!(eval_string("(list-tail '(a b c) 3)"), "()");
+        assert_eq!(eval_string("(cadr '(1 2 3))"), "2");
+        assert_eq!(eval_string("(cddr '(1 2 3))"), "(3)");
+        assert_eq!(eval_string("(caadr '(1 (2 3)))"), "2");
+        assert_eq!(eval_string("(cadddr '(1 2 3 4))"), "4");
+        // the last argument of append is shared rather than copied
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define tail '(3))
+            (set-car! (cdr (append '(1) tail)) 4)
+            tail
+        "#
+            ),
+            *eval_result("'(4)")
+        );
+        assert_eq!(
+            eval_error("(length '(1 . 2))"),
+            RuntimeErrorKind::NotAList(Rc::new(Value::pair(
+                Rc::new(Value::i64(1)),
+                Rc::new(Value::i64(2))
+            )))
+        );
+        assert_eq!(
+            eval_error("(reverse 1)"),
+            RuntimeErrorKind::NotAList(Rc::new(Value::i64(1)))
+        );
+        assert_eq!(
+            eval_error("(car nil)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "pair",
+                found: Rc::new(Value::nil())
+            }
+        );
+        assert!(matches!(
+            eval_error("(list-ref '(1 2) 2)"),
+            RuntimeErrorKind::IndexOutOfRange { index, .. } if *index == Value::i64(2)
+        ));
+        assert!(matches!(
+            eval_error("(list-tail '(1 2) -1)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "index",
+                ..
+            }
+        ));
+        assert!(matches!(
+            eval_error("(list-ref '(1 . 2) 1)"),
+            RuntimeErrorKind::NotAList(_)
+        ));
+        // indices which aren't exact integers are the wrong type, and big integers are too large
+        // to be in range
+        assert!(matches!(
+            eval_error("(list-ref '(1 2) 1.0)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "index",
+                ..
+            }
+        ));
+        assert!(matches!(
+            eval_error(r#"(string-ref "abc" -100000000000000000000)"#),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "index",
+                ..
+            }
+        ));
+        for expression in [
+            "(list-tail '(1 2) 100000000000000000000)",
+            r#"(substring "abc" 0 100000000000000000000)"#,
+        ] {
+            assert!(matches!(
+                eval_error(expression),
+                RuntimeErrorKind::IndexOutOfRange { index, .. }
+                    if index.to_string() == "100000000000000000000"
+            ));
+        }
+    }
+
+    #[test]
+    fn higher_order_built_ins() {
+        let eval_string = |string| eval_result(string).to_string();
+        assert_eq!(
+            eval_string("(map (lambda (x) (* x x)) '(1 2 3))"),
+            "(1 4 9)"
+        );
+        assert_eq!(eval_string("(map + '(1 2 3) '(10 20))"), "(11 22)");
+        assert_eq!(eval_string("(map car '((a 1) (b 2)))"), "(a b)");
+        assert_eq!(
+            eval_string(
+                r#"
+            (define total 0)
+            (for-each (lambda (x y) (set! total (+ total (* x y)))) '(1 2) '(3 4))
+            total
+        "#
+            ),
+            "11"
+        );
+        assert_eq!(
+            eval_string("(filter (lambda (x) (< x 3)) '(1 5 2 4))"),
+            "(1 2)"
+        );
+        assert_eq!(eval_string("(reduce + 0 '(1 2 3 4))"), "10");
+        assert_eq!(eval_string("(reduce + 0 nil)"), "0");
+        assert_eq!(eval_string("(reduce list 0 '(1 2 3))"), "(3 (2 1))");
+        assert_eq!(
+            eval_string("(fold-left list nil '(1 2 3))"),
+            "(((() 1) 2) 3)"
+        );
+        assert_eq!(
+            eval_string("(fold-right list nil '(1 2 3))"),
+            "(1 (2 (3 ())))"
+        );
+        assert_eq!(eval_string("(fold-right cons nil '(1 2 3))"), "(1 2 3)");
+        assert_eq!(eval_string("(fold-left + 0 '(1 2) '(10 20))"), "33");
+        assert_eq!(eval_string("(apply + '(1 2 3))"), "6");
+        assert_eq!(eval_string("(apply list 1 2 '(3 4))"), "(1 2 3 4)");
+        assert_eq!(eval_string("(apply (lambda args args) nil)"), "()");
+        assert_eq!(eval_string("(assoc 'b '((a 1) (b 2)))"), "(b 2)");
+        assert_eq!(
+            eval_string("(assoc \"b\" '((\"a\" 1) (\"b\" 2)))"),
+            "(\"b\" 2)"
+        );
+        assert_eq!(eval_string("(assoc 'c '((a 1) (b 2)))"), "false");
+        assert_eq!(eval_string("(assoc 2.0 '((1 a) (2 b)) =)"), "(2 b)");
+        assert_eq!(eval_string("(member '(1) '(a (1) b))"), "((1) b)");
+        assert_eq!(eval_string("(member 2.0 '(1 2 3) =)"), "(2 3)");
+        assert_eq!(eval_string("(member 4 '(1 2 3))"), "false");
+        assert_eq!(eval_string("(sort '(3 1 2 5 4) <)"), "(1 2 3 4 5)");
+        assert_eq!(eval_string("(sort nil <)"), "()");
+        // the sort is stable
+        assert_eq!(
+            eval_string("(sort '((b 2) (a 1) (c 2) (d 1)) (lambda (x y) (< (cadr x) (cadr y))))"),
+            "((a 1) (d 1) (b 2) (c 2))"
+        );
+        // apply calls its function in tail position
+        assert_eq!(
+            eval_string(
+                r#"
+            (define count-down (lambda (n)
+                (if (= n 0)
+                    'done
+                    (apply count-down (list (- n 1))))))
+            (count-down 100000)
+        "#
+            ),
+            "done"
+        );
+        assert_eq!(
+            eval_error("(map car 1)"),
+            RuntimeErrorKind::NotAList(Rc::new(Value::i64(1)))
+        );
+        assert_eq!(
+            eval_error("(map 1 '(1))"),
+            RuntimeErrorKind::NotCallable(Rc::new(Value::i64(1)))
+        );
+        assert_eq!(
+            eval_error("(apply + 1)"),
+            RuntimeErrorKind::NotAList(Rc::new(Value::i64(1)))
+        );
+        assert_eq!(
+            eval_error("(sort '(1 a) <)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "number",
+                found: Rc::new(Value::symbol("a"))
+            }
+        );
+    }
+
+    #[test]
+    fn predicates() {
+        for (expression, expected) in [
+            ("(null? nil)", true),
+            ("(null? '(1))", false),
+            ("(pair? '(1))", true),
+            ("(pair? nil)", false),
+            ("(list? '(1 2))", true),
+            ("(list? nil)", true),
+            ("(list? '(1 . 2))", false),
+            ("(symbol? 'a)", true),
+            ("(symbol? \"a\")", false),
+            ("(string? \"a\")", true),
+            ("(number? 1)", true),
+            ("(number? 1/2)", true),
+            ("(number? 1.5)", true),
+            ("(number? 'one)", false),
+            ("(boolean? false)", true),
+            ("(boolean? nil)", false),
+            ("(procedure? car)", true),
+            ("(procedure? (lambda (x) x))", true),
+            ("(procedure? 'car)", false),
+            ("(begin (defmacro m () 1) (procedure? m))", false),
+        ] {
+            assert_eq!(
+                *eval_result(expression),
+                Value::bool(expected),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn equality() {
+        for (expression, expected) in [
+            ("(eq? 'a 'a)", true),
+            ("(eq? nil '())", true),
+            ("(eq? true true)", true),
+            ("(eq? '(1) '(1))", false),
+            ("(let ((x '(1))) (eq? x x))", true),
+            ("(let ((x \"a\")) (eq? x x))", true),
+            ("(eq? \"a\" \"a\")", false),
+            ("(eqv? 2 2)", true),
+            ("(eqv? 100000000000000000000 100000000000000000000)", true),
+            ("(eqv? 1/2 2/4)", true),
+            ("(eqv? 1.5 1.5)", true),
+            ("(eqv? 1 1.0)", false),
+            ("(eqv? \"a\" \"a\")", false),
+            ("(eqv? '(1) '(1))", false),
+            ("(eqv? car car)", true),
+            (
+                "(equal? '(1 (2 \"three\") . 4) '(1 (2 \"three\") . 4))",
+                true,
+            ),
+            ("(equal? '(1 2) '(1 2 3))", false),
+            ("(equal? \"a\" \"a\")", true),
+            ("(equal? 1 1.0)", false),
+        ] {
+            assert_eq!(
+                *eval_result(expression),
+                Value::bool(expected),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn strings() {
+        for (expression, expected) in [
+            (r#"(string-length "héllo")"#, "5"),
+            (r#"(substring "héllo wörld" 1 4)"#, r#""éll""#),
+            (r#"(substring "héllo" 2)"#, r#""llo""#),
+            (r#"(string-append "a" "bc" "" "d")"#, r#""abcd""#),
+            (r#"(string-append)"#, r#""""#),
+            (r#"(string-ref "日本語" 1)"#, r#"#\本"#),
+            (r#"(string-split "a,b,,c" ",")"#, r#"("a" "b" "" "c")"#),
+            (
+                r#"(string-split "  one two\nthree ")"#,
+                r#"("one" "two" "three")"#,
+            ),
+            (r#"(string-join '("a" "b" "c") ", ")"#, r#""a, b, c""#),
+            (r#"(string-join '("a" "b"))"#, r#""a b""#),
+            (r#"(string-join nil)"#, r#""""#),
+            (r#"(string-upcase "straße")"#, r#""STRASSE""#),
+            (r#"(string-downcase "ÀB")"#, r#""àb""#),
+            (r#"(string-trim "  a b \n")"#, r#""a b""#),
+            (r#"(string-contains "héllo" "llo")"#, "2"),
+            (r#"(string-contains "hello" "x")"#, "false"),
+            (r#"(string-replace "a-b-c" "-" "+")"#, r#""a+b+c""#),
+            (r#"(string->list "a é")"#, r#"(#\a #\space #\é)"#),
+            (r#"(string->symbol "foo")"#, "foo"),
+            (r#"(symbol->string 'foo)"#, r#""foo""#),
+            (r#"(number->string 42)"#, r#""42""#),
+            (r#"(number->string 1/2)"#, r#""1/2""#),
+            (r#"(number->string 1.5)"#, r#""1.5""#),
+            (r#"(string->number "42")"#, "42"),
+            (r#"(string->number "-1e3")"#, "-1000.0"),
+            (r#"(string->number "6/4")"#, "3/2"),
+            (r#"(string->number "abc")"#, "false"),
+            (r#"(string->number "1 2")"#, "false"),
+        ] {
+            assert_eq!(
+                eval_result(expression).to_string(),
+                expected,
+                "{}",
+                expression
+            );
+        }
+        assert_eq!(
+            *eval_result(r#"(eq? (string->symbol "foo") 'foo)"#),
+            Value::bool(true)
+        );
+        assert!(matches!(
+            eval_error(r#"(string-ref "héllo" 5)"#),
+            RuntimeErrorKind::IndexOutOfRange { index, .. } if *index == Value::i64(5)
+        ));
+        assert!(matches!(
+            eval_error(r#"(substring "abc" 2 1)"#),
+            RuntimeErrorKind::IndexOutOfRange { index, .. } if *index == Value::i64(2)
+        ));
+        assert!(matches!(
+            eval_error(r#"(substring "abc" 0 4)"#),
+            RuntimeErrorKind::IndexOutOfRange { index, .. } if *index == Value::i64(4)
+        ));
+        assert_eq!(
+            eval_error("(string-length 'abc)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "string",
+                found: Rc::new(Value::symbol("abc"))
+            }
+        );
+        assert!(matches!(
+            eval_error(r#"(string-split "abc" "")"#),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "non-empty string",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn chars() {
+        for (expression, expected) in [
+            (r#"#\a"#, r#"#\a"#),
+            (r#"(char->integer #\A)"#, "65"),
+            (r#"(char->integer #\newline)"#, "10"),
+            (r#"(integer->char 955)"#, r#"#\λ"#),
+            (r#"(integer->char 32)"#, r#"#\space"#),
+            (r#"(char-upcase #\a)"#, r#"#\A"#),
+            (r#"(char-upcase #\ß)"#, r#"#\ß"#),
+            (r#"(char-downcase #\Λ)"#, r#"#\λ"#),
+            (r#"(char-alphabetic? #\é)"#, "true"),
+            (r#"(char-alphabetic? #\1)"#, "false"),
+            (r#"(char-numeric? #\7)"#, "true"),
+            (r#"(char-whitespace? #\tab)"#, "true"),
+            (r#"(char-whitespace? #\x)"#, "false"),
+            (r#"(char? #\x41)"#, "true"),
+            (r#"(char? "a")"#, "false"),
+            (r#"(eqv? #\a (string-ref "abc" 0))"#, "true"),
+        ] {
+            assert_eq!(
+                eval_result(expression).to_string(),
+                expected,
+                "{}",
+                expression
+            );
+        }
+        assert_eq!(
+            eval_error(r#"(char->integer "a")"#),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "char",
+                found: Rc::new(Value::string("a"))
+            }
+        );
+        assert_eq!(
+            eval_error("(integer->char 55296)"),
+            RuntimeErrorKind::TypeMismatch {
+                expected: "character code",
+                found: Rc::new(Value::i64(55296))
+            }
+        );
+    }
+
+    #[test]
+    fn long_lists_are_dropped() {
+        assert_eq!(
+            *eval_result(
+                r#"
+            (define l (let loop ((i 0) (l '()))
+                (if (= i 100000) l (loop (+ i 1) (cons i l)))))
+            (define l 0)
+            l
+        "#
+            ),
+            Value::i64(0)
+        );
+    }
+
+    #[test]
+    fn long_lists_are_compared() {
+        assert_eq!(
+            eval_result(
+                r#"
+            (define make-list (lambda (n)
+                (let loop ((i 0) (l '()))
+                    (if (= i n) l (loop (+ i 1) (cons i l))))))
+            (define a (make-list 100000))
+            (define b (make-list 100000))
+            (list (equal? a b) (equal? a (cons 0 b)))
+        "#
+            )
+            .to_string(),
+            "(true false)"
+        );
+    }
 }
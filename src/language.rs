@@ -8,16 +8,57 @@ mod keyword {
     const DEFINE: &str = "define";
     const LAMBDA: &str = "lambda";
     const IF: &str = "if";
+    const SET: &str = "set!";
+    const LET: &str = "let";
+    const LET_STAR: &str = "let*";
+    const LETREC: &str = "letrec";
+    const LETREC_STAR: &str = "letrec*";
+    const BEGIN: &str = "begin";
+    const COND: &str = "cond";
+    const CASE: &str = "case";
+    const AND: &str = "and";
+    const OR: &str = "or";
+    const WHEN: &str = "when";
+    const UNLESS: &str = "unless";
+    const QUASIQUOTE: &str = "quasiquote";
+    const UNQUOTE: &str = "unquote";
+    const UNQUOTE_SPLICING: &str = "unquote-splicing";
+    const DEFMACRO: &str = "defmacro";
+    const MACROEXPAND: &str = "macroexpand";
+    const MACROEXPAND_1: &str = "macroexpand-1";
+    const DEFINE_SYNTAX: &str = "define-syntax";
+    const SYNTAX_RULES: &str = "syntax-rules";
 
     pub fn quote() -> Value {
         Value::symbol(QUOTE)
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub enum Keyword {
         Quote,
         Define,
         Lambda,
         If,
+        Set,
+        Let,
+        LetStar,
+        Letrec,
+        LetrecStar,
+        Begin,
+        Cond,
+        Case,
+        And,
+        Or,
+        When,
+        Unless,
+        Quasiquote,
+        Unquote,
+        UnquoteSplicing,
+        Defmacro,
+        Macroexpand,
+        Macroexpand1,
+        DefineSyntax,
+        SyntaxRules,
     }
 
     impl Keyword {
@@ -28,17 +69,99 @@ mod keyword {
                 DEFINE => Some(Define),
                 LAMBDA => Some(Lambda),
                 IF => Some(If),
+                SET => Some(Set),
+                LET => Some(Let),
+                LET_STAR => Some(LetStar),
+                LETREC => Some(Letrec),
+                LETREC_STAR => Some(LetrecStar),
+                BEGIN => Some(Begin),
+                COND => Some(Cond),
+                CASE => Some(Case),
+                AND => Some(And),
+                OR => Some(Or),
+                WHEN => Some(When),
+                UNLESS => Some(Unless),
+                QUASIQUOTE => Some(Quasiquote),
+                UNQUOTE => Some(Unquote),
+                UNQUOTE_SPLICING => Some(UnquoteSplicing),
+                DEFMACRO => Some(Defmacro),
+                MACROEXPAND => Some(Macroexpand),
+                MACROEXPAND_1 => Some(Macroexpand1),
+                DEFINE_SYNTAX => Some(DefineSyntax),
+                SYNTAX_RULES => Some(SyntaxRules),
                 _ => None,
             }
         }
+
+        pub fn as_str(self) -> &'static str {
+            use Keyword::*;
+            match self {
+                Quote => QUOTE,
+                Define => DEFINE,
+                Lambda => LAMBDA,
+                If => IF,
+                Set => SET,
+                Let => LET,
+                LetStar => LET_STAR,
+                Letrec => LETREC,
+                LetrecStar => LETREC_STAR,
+                Begin => BEGIN,
+                Cond => COND,
+                Case => CASE,
+                And => AND,
+                Or => OR,
+                When => WHEN,
+                Unless => UNLESS,
+                Quasiquote => QUASIQUOTE,
+                Unquote => UNQUOTE,
+                UnquoteSplicing => UNQUOTE_SPLICING,
+                Defmacro => DEFMACRO,
+                Macroexpand => MACROEXPAND,
+                Macroexpand1 => MACROEXPAND_1,
+                DefineSyntax => DEFINE_SYNTAX,
+                SyntaxRules => SYNTAX_RULES,
+            }
+        }
     }
 }
 
 pub use keyword::Keyword;
 
+/// Marks the start of the optional parameters in the parameter list of a lambda
+pub const OPTIONAL: &str = "#!optional";
+
+/// Precedes a character literal, such as `#\a`
+pub const CHAR_PREFIX: &str = "#\\";
+
+/// Characters written by name, as in `#\space`
+pub const CHAR_NAMES: &[(&str, char)] = &[
+    ("space", ' '),
+    ("newline", '\n'),
+    ("tab", '\t'),
+    ("nul", '\0'),
+];
+
+/// Symbols with a special meaning inside the clauses of `cond` and `case`
+pub mod clause {
+    pub const ELSE: &str = "else";
+    pub const ARROW: &str = "=>";
+}
+
 pub fn quote_value(value: Value) -> Value {
+    quote_value_rc(Rc::new(value))
+}
+
+pub fn quote_value_rc(value: Rc<Value>) -> Value {
     Value::pair(
         Rc::new(keyword::quote()),
-        Rc::new(Value::pair(Rc::new(value), Rc::new(Value::nil()))),
+        Rc::new(Value::pair(value, Rc::new(Value::nil()))),
+    )
+}
+
+/// Builds a form such as `(quasiquote value)` from the abbreviation `` `value``
+pub fn prefix_form(keyword: Keyword, value: Rc<Value>) -> Value {
+    Value::pair(
+        Rc::new(Value::symbol(keyword.as_str())),
+        Rc::new(Value::pair(value, Rc::new(Value::nil()))),
     )
 }
@@ -1,10 +1,10 @@
-use crate::value::{Atom, Pair, Value};
+use crate::value::{Atom, Value};
 use std::{borrow::Borrow, rc::Rc};
 
 pub fn from_vec_with_end(mut vec: Vec<Value>, end: Value) -> Value {
     let mut list = end;
     for value in vec.drain(..).rev() {
-        list = Value::Pair(Pair(Rc::new(value), Rc::new(list)));
+        list = Value::pair(Rc::new(value), Rc::new(list));
     }
     list
 }
@@ -13,70 +13,137 @@ pub fn from_vec(vec: Vec<Value>) -> Value {
     from_vec_with_end(vec, Value::Atom(Atom::Nil))
 }
 
-pub fn to_vec<L: Borrow<Value>>(list: L) -> Vec<Rc<Value>> {
-    let mut list = list.borrow();
+pub fn from_rc_vec_with_end(vec: Vec<Rc<Value>>, end: Rc<Value>) -> Rc<Value> {
+    let mut list = end;
+    for value in vec.into_iter().rev() {
+        list = Rc::new(Value::pair(value, list));
+    }
+    list
+}
+
+pub fn from_rc_vec(vec: Vec<Rc<Value>>) -> Rc<Value> {
+    from_rc_vec_with_end(vec, Rc::new(Value::nil()))
+}
+
+/// Detects a circular list while following its cdrs, by moving through the list a second time
+/// at half the speed. The two positions can only meet if the list is circular.
+pub struct CycleCheck {
+    slow: Rc<Value>,
+    steps: usize,
+}
+
+impl CycleCheck {
+    /// Starts checking from `tail`, which should be the first value passed to `is_cycle`
+    pub fn new(tail: &Rc<Value>) -> Self {
+        Self {
+            slow: tail.clone(),
+            steps: 0,
+        }
+    }
+
+    /// Called with each successive tail of the list after the first, returning true once the
+    /// list is known to be circular
+    pub fn is_cycle(&mut self, tail: &Rc<Value>) -> bool {
+        self.steps += 1;
+        if self.steps.is_multiple_of(2) {
+            if let Value::Pair(pair) = &*self.slow {
+                self.slow = pair.cdr();
+            }
+        }
+        Rc::ptr_eq(tail, &self.slow)
+    }
+}
+
+/// The elements of a proper list, or `None` if the value is an improper or circular list
+pub fn to_vec<L: Borrow<Value>>(list: L) -> Option<Vec<Rc<Value>>> {
     let mut ret = Vec::new();
+    let mut tail = match list.borrow() {
+        Value::Atom(Atom::Nil) => return Some(ret),
+        Value::Pair(pair) => {
+            ret.push(pair.car());
+            pair.cdr()
+        }
+        _ => return None,
+    };
+    let mut cycle_check = CycleCheck::new(&tail);
     loop {
-        match list {
-            Value::Atom(Atom::Nil) => break ret,
-            Value::Pair(Pair(head, tail)) => {
-                ret.push(head.clone());
-                list = &**tail;
+        tail = match &*tail {
+            Value::Atom(Atom::Nil) => break Some(ret),
+            Value::Pair(pair) => {
+                ret.push(pair.car());
+                pair.cdr()
             }
-            _ => panic!("not a list"),
+            _ => break None,
+        };
+        if cycle_check.is_cycle(&tail) {
+            break None;
         }
     }
 }
 
-pub fn is_list(value: &Rc<Value>) -> bool {
-    match &**value {
-        Value::Atom(Atom::Nil) => true,
-        Value::Pair(Pair(_, tail)) => is_list(&*tail),
-        _ => false,
+/// Splits a possibly improper list into its elements and the value ending it (nil for a proper
+/// list)
+pub fn to_vec_with_end(list: &Rc<Value>) -> (Vec<Rc<Value>>, Rc<Value>) {
+    let mut ret = Vec::new();
+    let mut tail = list.clone();
+    while let Value::Pair(pair) = &*tail {
+        ret.push(pair.car());
+        let cdr = pair.cdr();
+        tail = cdr;
     }
+    (ret, tail)
 }
 
-pub fn length(value: &Rc<Value>) -> usize {
-    match &**value {
-        Value::Atom(Atom::Nil) => 0,
-        Value::Pair(Pair(_, tail)) => 1 + length(&*tail),
-        _ => panic!("not a list"),
-    }
+/// Whether a value is a proper list, which excludes circular lists
+pub fn is_list(value: &Rc<Value>) -> bool {
+    length(value).is_some()
 }
 
-pub fn map<F: FnMut(&Rc<Value>) -> Rc<Value>>(list: &Rc<Value>, mut f: F) -> Rc<Value> {
-    match &**list {
-        Value::Atom(Atom::Nil) => list.clone(),
-        Value::Pair(Pair(head, tail)) => Rc::new(Value::Pair(Pair(f(head), map(tail, f)))),
-        _ => panic!("not a list"),
+/// The number of elements of a proper list, or `None` for an improper or circular list
+pub fn length(value: &Rc<Value>) -> Option<usize> {
+    let mut length = 0;
+    let mut tail = value.clone();
+    let mut cycle_check = CycleCheck::new(&tail);
+    loop {
+        tail = match &*tail {
+            Value::Atom(Atom::Nil) => break Some(length),
+            Value::Pair(pair) => pair.cdr(),
+            _ => break None,
+        };
+        length += 1;
+        if cycle_check.is_cycle(&tail) {
+            break None;
+        }
     }
 }
 
-pub fn split_head<L: Borrow<Value>>(list: L) -> (Rc<Value>, Rc<Value>) {
+pub fn map<F: FnMut(&Rc<Value>) -> Rc<Value>>(list: &Rc<Value>, f: F) -> Option<Rc<Value>> {
+    to_vec(&**list).map(|values| from_rc_vec(values.iter().map(f).collect()))
+}
+
+pub fn split_head<L: Borrow<Value>>(list: L) -> Option<(Rc<Value>, Rc<Value>)> {
     match list.borrow() {
-        Value::Atom(Atom::Nil) => panic!("list is empty"),
-        Value::Pair(Pair(first, rest)) => (first.clone(), rest.clone()),
-        _ => panic!("not a list"),
+        Value::Pair(pair) => Some((pair.car(), pair.cdr())),
+        _ => None,
     }
 }
 
-pub fn head(list: &Rc<Value>) -> Rc<Value> {
+pub fn head(list: &Rc<Value>) -> Option<Rc<Value>> {
     match &**list {
-        Value::Atom(Atom::Nil) => panic!("list is empty"),
-        Value::Pair(Pair(first, _)) => first.clone(),
-        _ => panic!("not a list"),
+        Value::Pair(pair) => Some(pair.car()),
+        _ => None,
     }
 }
 
-pub fn take2<L: Borrow<Value>>(list: L) -> (Rc<Value>, Rc<Value>) {
-    let (first, rest) = split_head(list.borrow());
-    let second = head(&rest);
-    (first, second)
+pub fn take2<L: Borrow<Value>>(list: L) -> Option<(Rc<Value>, Rc<Value>)> {
+    let (first, rest) = split_head(list.borrow())?;
+    let second = head(&rest)?;
+    Some((first, second))
 }
 
-pub fn take3<L: Borrow<Value>>(list: L) -> (Rc<Value>, Rc<Value>, Rc<Value>) {
-    let (first, rest) = split_head(list);
-    let (second, rest) = split_head(rest);
-    let third = head(&rest);
-    (first, second, third)
+pub fn take3<L: Borrow<Value>>(list: L) -> Option<(Rc<Value>, Rc<Value>, Rc<Value>)> {
+    let (first, rest) = split_head(list)?;
+    let (second, rest) = split_head(rest)?;
+    let third = head(&rest)?;
+    Some((first, second, third))
 }
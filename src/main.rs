@@ -1,31 +1,92 @@
 #![allow(dead_code)]
 mod built_in;
+mod cli;
 mod error;
 mod eval;
 mod language;
 mod list;
+mod number;
 mod parse;
 mod pretty;
+mod repl;
+mod source;
+mod syntax_rules;
 mod value;
 
+use cli::{Args, Input};
+use source::Source;
 use std::{
-    io::{self, BufRead},
+    fs,
+    io::{self, IsTerminal, Read},
+    process,
     rc::Rc,
 };
+use value::Value;
 
-fn main() -> io::Result<()> {
-    let mut buffer = String::new();
-    let stdin = io::stdin();
-    let handle = stdin.lock();
-    for line in handle.lines() {
-        if let Ok(string) = line.as_ref() {
-            buffer.push_str(string.as_str());
+fn read_input(input: &Input) -> io::Result<Rc<Source>> {
+    match input {
+        Input::File(path) => Ok(Source::new(
+            path.display().to_string(),
+            fs::read_to_string(path)?,
+        )),
+        Input::Stdin => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            Ok(Source::new("<stdin>", text))
+        }
+        Input::Expr(expr) => Ok(Source::new("<expr>", expr.as_str())),
+    }
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    let mut inputs = args.inputs.clone();
+    let mut interactive = args.interactive;
+    if inputs.is_empty() && !interactive {
+        if io::stdin().is_terminal() {
+            interactive = true;
+        } else {
+            inputs.push(Input::Stdin);
         }
     }
-    let ast = parse::parse(buffer.as_str()).unwrap();
     let mut runtime = eval::Runtime::new();
-    for v in ast {
-        runtime.eval(&Rc::new(v));
+    runtime.define_top_level_variable(
+        "command-line-arguments",
+        Rc::new(list::from_vec(
+            args.script_args.iter().map(Value::string).collect(),
+        )),
+    );
+    for input in &inputs {
+        let source = match read_input(input) {
+            Ok(source) => source,
+            Err(error) => {
+                if let Input::File(path) = input {
+                    eprintln!("{}: {}", path.display(), error);
+                } else {
+                    eprintln!("{}", error);
+                }
+                process::exit(1);
+            }
+        };
+        if let Err(error) = runtime.eval_source(&source) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+    if interactive {
+        if let Err(error) = repl::run(runtime) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
     }
-    Ok(())
 }
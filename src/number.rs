@@ -0,0 +1,384 @@
+use crate::value::{Atom, Value};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+
+/// A numeric atom. Integers are exact, and are stored as i64 where they fit, with arithmetic on
+/// them promoted to arbitrary precision when the result would overflow. Fractions are exact
+/// rationals, which are demoted to integers when their denominator is one. Floats are inexact,
+/// and arithmetic involving one is performed on floats.
+#[derive(Clone, Debug)]
+pub enum Number {
+    I64(i64),
+    BigInt(BigInt),
+    Rational(BigRational),
+    F64(f64),
+}
+
+impl Number {
+    pub fn from_atom(atom: &Atom) -> Option<Self> {
+        match atom {
+            Atom::I64(i) => Some(Self::I64(*i)),
+            Atom::BigInt(i) => Some(Self::BigInt(i.clone())),
+            Atom::Rational(r) => Some(Self::Rational(r.clone())),
+            Atom::F64(x) => Some(Self::F64(*x)),
+            _ => None,
+        }
+    }
+
+    /// Stores big integers which fit in an i64 as i64, so each integer has a single
+    /// representation
+    fn normalize(i: BigInt) -> Self {
+        match i.to_i64() {
+            Some(i) => Self::I64(i),
+            None => Self::BigInt(i),
+        }
+    }
+
+    /// Stores rationals with a denominator of one as integers
+    fn normalize_rational(r: BigRational) -> Self {
+        if r.is_integer() {
+            Self::normalize(r.to_integer())
+        } else {
+            Self::Rational(r)
+        }
+    }
+
+    /// The fraction `numerator/denominator`. The denominator must not be zero.
+    pub fn rational(numerator: BigInt, denominator: BigInt) -> Self {
+        Self::normalize_rational(BigRational::new(numerator, denominator))
+    }
+
+    pub fn into_atom(self) -> Atom {
+        match self {
+            Self::I64(i) => Atom::I64(i),
+            Self::BigInt(i) => Atom::BigInt(i),
+            Self::Rational(r) => Atom::Rational(r),
+            Self::F64(x) => Atom::F64(x),
+        }
+    }
+
+    pub fn into_value(self) -> Value {
+        Value::Atom(self.into_atom())
+    }
+
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, Self::F64(_))
+    }
+
+    fn to_rational(&self) -> Option<BigRational> {
+        match self {
+            Self::I64(i) => Some(BigRational::from_integer(BigInt::from(*i))),
+            Self::BigInt(i) => Some(BigRational::from_integer(i.clone())),
+            Self::Rational(r) => Some(r.clone()),
+            Self::F64(_) => None,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Self::I64(i) => *i as f64,
+            Self::BigInt(i) => i.to_f64().unwrap_or(f64::NAN),
+            Self::Rational(r) => r.to_f64().unwrap_or(f64::NAN),
+            Self::F64(x) => *x,
+        }
+    }
+
+    pub fn to_inexact(&self) -> Self {
+        Self::F64(self.to_f64())
+    }
+
+    /// Applies `checked` if both numbers are i64, falling back to `exact` if both are exact but
+    /// either isn't an i64 or the result overflows, and to `float` if either is a float
+    fn binary(
+        &self,
+        other: &Self,
+        checked: fn(i64, i64) -> Option<i64>,
+        exact: fn(BigRational, BigRational) -> BigRational,
+        float: fn(f64, f64) -> f64,
+    ) -> Self {
+        if let (Self::I64(lhs), Self::I64(rhs)) = (self, other) {
+            if let Some(result) = checked(*lhs, *rhs) {
+                return Self::I64(result);
+            }
+        }
+        match (self.to_rational(), other.to_rational()) {
+            (Some(lhs), Some(rhs)) => Self::normalize_rational(exact(lhs, rhs)),
+            _ => Self::F64(float(self.to_f64(), other.to_f64())),
+        }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        self.binary(
+            other,
+            i64::checked_add,
+            |lhs, rhs| lhs + rhs,
+            |lhs, rhs| lhs + rhs,
+        )
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.binary(
+            other,
+            i64::checked_sub,
+            |lhs, rhs| lhs - rhs,
+            |lhs, rhs| lhs - rhs,
+        )
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        self.binary(
+            other,
+            i64::checked_mul,
+            |lhs, rhs| lhs * rhs,
+            |lhs, rhs| lhs * rhs,
+        )
+    }
+
+    /// Division, which is exact if both numbers are exact. The divisor must not be exact zero.
+    pub fn div(&self, other: &Self) -> Self {
+        self.binary(
+            other,
+            |lhs, rhs| {
+                if lhs.checked_rem(rhs)? == 0 {
+                    lhs.checked_div(rhs)
+                } else {
+                    None
+                }
+            },
+            |lhs, rhs| lhs / rhs,
+            |lhs, rhs| lhs / rhs,
+        )
+    }
+
+    /// Division truncating towards zero. The divisor must not be zero.
+    pub fn quotient(&self, other: &Self) -> Self {
+        self.binary(
+            other,
+            i64::checked_div,
+            |lhs, rhs| (lhs / rhs).trunc(),
+            |lhs, rhs| (lhs / rhs).trunc(),
+        )
+    }
+
+    /// The remainder of truncating division, which has the sign of the dividend. The divisor
+    /// must not be zero.
+    pub fn remainder(&self, other: &Self) -> Self {
+        self.binary(
+            other,
+            i64::checked_rem,
+            |lhs, rhs| lhs % rhs,
+            |lhs, rhs| lhs % rhs,
+        )
+    }
+
+    /// The remainder of flooring division, which has the sign of the divisor. The divisor must
+    /// not be zero.
+    pub fn modulo(&self, other: &Self) -> Self {
+        let remainder = self.remainder(other);
+        if !remainder.is_zero() && remainder.is_negative() != other.is_negative() {
+            remainder.add(other)
+        } else {
+            remainder
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        match self {
+            Self::I64(i) => match i.checked_abs() {
+                Some(i) => Self::I64(i),
+                None => Self::normalize(BigInt::from(*i).abs()),
+            },
+            Self::BigInt(i) => Self::BigInt(i.abs()),
+            Self::Rational(r) => Self::Rational(r.abs()),
+            Self::F64(x) => Self::F64(x.abs()),
+        }
+    }
+
+    /// Raises to a power. The result is exact if the base is exact and the exponent is an
+    /// integer, unless the base is zero and the exponent negative. Returns `None` if the result
+    /// would be exact but the exponent is too large to compute it, which is only possible when
+    /// the base isn't zero, one or minus one.
+    pub fn pow(&self, exponent: &Self) -> Option<Self> {
+        let base = self.to_rational();
+        let exponent_integer = match exponent {
+            Self::I64(_) | Self::BigInt(_) => exponent.to_rational().map(|e| e.to_integer()),
+            _ => None,
+        };
+        if let (Some(base), Some(exponent)) = (base, exponent_integer) {
+            match exponent.to_i32() {
+                Some(exponent) if exponent >= 0 || !base.is_zero() => {
+                    return Some(Self::normalize_rational(base.pow(exponent)));
+                }
+                Some(_) => (),
+                None if base.is_one() => return Some(Self::I64(1)),
+                None if base == -BigRational::one() => {
+                    return Some(Self::I64(if exponent.bit(0) { -1 } else { 1 }));
+                }
+                None if base.is_zero() && exponent.is_positive() => return Some(Self::I64(0)),
+                None if !base.is_zero() => return None,
+                None => (),
+            }
+        }
+        Some(Self::F64(self.to_f64().powf(exponent.to_f64())))
+    }
+
+    /// The square root, which is exact for exact squares
+    pub fn sqrt(&self) -> Self {
+        if let Some(r) = self.to_rational() {
+            if !r.is_negative() {
+                let numerator = r.numer().sqrt();
+                let denominator = r.denom().sqrt();
+                if &numerator * &numerator == *r.numer()
+                    && &denominator * &denominator == *r.denom()
+                {
+                    return Self::rational(numerator, denominator);
+                }
+            }
+        }
+        Self::F64(self.to_f64().sqrt())
+    }
+
+    /// Rounds floats with `float` and fractions with `exact`, leaving integers as they are
+    pub fn round_with(
+        &self,
+        float: fn(f64) -> f64,
+        exact: fn(&BigRational) -> BigRational,
+    ) -> Self {
+        match self {
+            Self::Rational(r) => Self::normalize_rational(exact(r)),
+            Self::F64(x) => Self::F64(float(*x)),
+            _ => self.clone(),
+        }
+    }
+
+    /// The numerator of the number as a fraction in lowest terms, which is inexact if the number
+    /// is
+    pub fn numerator(&self) -> Self {
+        self.fraction_part(|r| r.numer().clone())
+    }
+
+    /// The denominator of the number as a fraction in lowest terms, which is inexact if the
+    /// number is
+    pub fn denominator(&self) -> Self {
+        self.fraction_part(|r| r.denom().clone())
+    }
+
+    fn fraction_part(&self, f: fn(&BigRational) -> BigInt) -> Self {
+        match self.to_rational() {
+            Some(r) => Self::normalize(f(&r)),
+            None => Self::F64(
+                BigRational::from_float(self.to_f64())
+                    .and_then(|r| f(&r).to_f64())
+                    .unwrap_or(f64::NAN),
+            ),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::I64(i) => *i == 0,
+            Self::BigInt(i) => i.is_zero(),
+            Self::Rational(r) => r.is_zero(),
+            Self::F64(x) => *x == 0.0,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Self::I64(i) => *i < 0,
+            Self::BigInt(i) => i.is_negative(),
+            Self::Rational(r) => r.is_negative(),
+            Self::F64(x) => *x < 0.0,
+        }
+    }
+}
+
+/// The exact counterpart of `f64::round_ties_even`
+pub fn round_ties_even(r: &BigRational) -> BigRational {
+    let floor = r.floor();
+    let fraction = r - &floor;
+    let half = BigRational::new(BigInt::one(), BigInt::from(2));
+    let is_odd = !(floor.to_integer() % BigInt::from(2)).is_zero();
+    if fraction > half || (fraction == half && is_odd) {
+        floor + BigRational::one()
+    } else {
+        floor
+    }
+}
+
+// Numbers are compared by value, so exact and inexact numbers can be equal, and NaN is not
+// equal to anything
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::I64(lhs), Self::I64(rhs)) => Some(lhs.cmp(rhs)),
+            _ => match (self.to_rational(), other.to_rational()) {
+                (Some(lhs), Some(rhs)) => Some(lhs.cmp(&rhs)),
+                _ => self.to_f64().partial_cmp(&other.to_f64()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Number;
+    use num_bigint::BigInt;
+
+    #[test]
+    fn promotion() {
+        let max = Number::I64(i64::MAX);
+        let one = Number::I64(1);
+        let big = max.add(&one);
+        assert!(matches!(&big, Number::BigInt(i) if *i == BigInt::from(i64::MAX) + 1));
+        // results which fit in an i64 are demoted again
+        assert!(matches!(big.sub(&one), Number::I64(i64::MAX)));
+        assert!(matches!(
+            Number::I64(i64::MIN).abs(),
+            Number::BigInt(i) if i == -BigInt::from(i64::MIN)
+        ));
+        assert_eq!(Number::I64(i64::MIN).quotient(&Number::I64(-1)), big);
+        assert_eq!(Number::I64(-7).modulo(&Number::I64(2)), Number::I64(1));
+        assert!(big > max);
+    }
+
+    #[test]
+    fn contagion() {
+        assert!(matches!(
+            Number::I64(1).add(&Number::F64(0.5)),
+            Number::F64(x) if x == 1.5
+        ));
+        assert!(matches!(
+            Number::I64(7).div(&Number::F64(2.0)),
+            Number::F64(x) if x == 3.5
+        ));
+        assert!(matches!(
+            Number::I64(2).pow(&Number::I64(3)),
+            Some(Number::I64(8))
+        ));
+        assert!(matches!(
+            Number::I64(2).pow(&Number::I64(-1)),
+            Some(Number::Rational(_))
+        ));
+        assert!(matches!(
+            Number::I64(0).pow(&Number::I64(-1)),
+            Some(Number::F64(x)) if x.is_infinite()
+        ));
+        assert!(matches!(Number::I64(16).sqrt(), Number::I64(4)));
+        assert!(matches!(Number::I64(2).sqrt(), Number::F64(_)));
+        assert!(matches!(
+            Number::F64(-7.5).modulo(&Number::I64(2)),
+            Number::F64(x) if x == 0.5
+        ));
+        assert_eq!(Number::I64(1), Number::F64(1.0));
+        assert_ne!(Number::F64(f64::NAN), Number::F64(f64::NAN));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::source::{Source, Span};
+use nom::{error::ErrorKind, Offset};
+use std::{fmt, rc::Rc};
+
+/// The error type used by the parser combinators. Positions are recorded as slices of the input
+/// and converted into spans once parsing is finished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadError<'a> {
+    pub input: &'a str,
+    pub kind: ReadErrorKind<'a>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReadErrorKind<'a> {
+    Nom(ErrorKind),
+    UnclosedList { open: &'a str },
+    UnterminatedString { open: &'a str },
+    UnterminatedBlockComment { open: &'a str },
+    InvalidEscape(char),
+    ZeroDenominator,
+    UnknownCharName(&'a str),
+}
+
+pub type ReadResult<'a, O> = nom::IResult<&'a str, O, ReadError<'a>>;
+
+impl<'a> ReadError<'a> {
+    pub fn new(input: &'a str, kind: ReadErrorKind<'a>) -> Self {
+        Self { input, kind }
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for ReadError<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        Self::new(input, ReadErrorKind::Nom(kind))
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    UnexpectedChar(char),
+    UnexpectedEndOfInput,
+    UnclosedList { open: Span, found: Option<char> },
+    UnterminatedString,
+    UnterminatedBlockComment,
+    InvalidEscape(char),
+    ZeroDenominator,
+    UnknownCharName(String),
+}
+
+/// A single problem found in the input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub kind: SyntaxErrorKind,
+    pub span: Span,
+}
+
+impl SyntaxError {
+    pub fn from_read_error(read_error: ReadError<'_>, source: &Rc<Source>) -> Self {
+        let span_of = |s: &str| {
+            let start = source.text.offset(s);
+            let end = s
+                .chars()
+                .next()
+                .map(|c| start + c.len_utf8())
+                .unwrap_or(start);
+            Span {
+                source: source.clone(),
+                start,
+                end,
+            }
+        };
+        let found = read_error.input.chars().next();
+        let (kind, span) = match read_error.kind {
+            ReadErrorKind::Nom(_) => (
+                found
+                    .map(SyntaxErrorKind::UnexpectedChar)
+                    .unwrap_or(SyntaxErrorKind::UnexpectedEndOfInput),
+                span_of(read_error.input),
+            ),
+            ReadErrorKind::UnclosedList { open } => (
+                SyntaxErrorKind::UnclosedList {
+                    open: span_of(open),
+                    found,
+                },
+                span_of(read_error.input),
+            ),
+            ReadErrorKind::UnterminatedString { open } => {
+                (SyntaxErrorKind::UnterminatedString, span_of(open))
+            }
+            ReadErrorKind::UnterminatedBlockComment { open } => {
+                (SyntaxErrorKind::UnterminatedBlockComment, span_of(open))
+            }
+            ReadErrorKind::InvalidEscape(c) => {
+                (SyntaxErrorKind::InvalidEscape(c), span_of(read_error.input))
+            }
+            ReadErrorKind::ZeroDenominator => {
+                (SyntaxErrorKind::ZeroDenominator, span_of(read_error.input))
+            }
+            ReadErrorKind::UnknownCharName(name) => (
+                SyntaxErrorKind::UnknownCharName(name.to_string()),
+                span_of(read_error.input),
+            ),
+        };
+        Self { kind, span }
+    }
+
+    /// Byte offset into the source at which the error occurred
+    pub fn offset(&self) -> usize {
+        self.span.start
+    }
+}
+
+impl fmt::Display for SyntaxErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SyntaxErrorKind::*;
+        match self {
+            UnexpectedChar(c) => write!(f, "unexpected '{}'", c),
+            UnexpectedEndOfInput => write!(f, "unexpected end of input, expected a value"),
+            UnclosedList { open, found } => {
+                let (line, col) = open.line_col();
+                write!(
+                    f,
+                    "expected closing ')' for list opened at {}:{}, found ",
+                    line, col
+                )?;
+                match found {
+                    Some(c) => write!(f, "'{}'", c),
+                    None => write!(f, "end of input"),
+                }
+            }
+            UnterminatedString => write!(f, "unterminated string literal"),
+            UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            InvalidEscape(c) => write!(f, "invalid escape \\{}", c),
+            ZeroDenominator => write!(f, "zero denominator in fraction"),
+            UnknownCharName(name) => write!(f, "unknown character name: {}", name),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}\n{}", self.span, self.kind, self.span.excerpt())
+    }
+}
+
+/// All the syntax errors found while parsing an input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub errors: Vec<SyntaxError>,
+}
+
+impl ParseError {
+    /// True if the input is valid apart from ending part-way through a datum, such that more input
+    /// could complete it
+    pub fn is_incomplete(&self) -> bool {
+        match self.errors.as_slice() {
+            [error] => matches!(
+                error.kind,
+                SyntaxErrorKind::UnclosedList { found: None, .. }
+                    | SyntaxErrorKind::UnterminatedString
+                    | SyntaxErrorKind::UnterminatedBlockComment
+                    | SyntaxErrorKind::UnexpectedEndOfInput
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,23 +1,31 @@
+mod error;
+mod space;
 mod string;
 
 mod atom {
-    use super::string;
-    use crate::value::Atom;
+    use super::{
+        error::{ReadError, ReadErrorKind, ReadResult},
+        string,
+    };
+    use crate::{language, number::Number, value::Atom};
     use nom::{
         branch::alt,
         bytes::complete::tag,
-        character::complete::{anychar, i64},
-        combinator::{map, recognize, value, verify},
+        character::complete::{anychar, char, digit0, digit1, hex_digit1, one_of},
+        combinator::{eof, map, map_opt, opt, peek, recognize, value, verify},
+        error::ErrorKind,
         multi::many0,
-        sequence::pair,
-        IResult,
+        sequence::{pair, preceded, separated_pair, terminated, tuple},
+        Err, Offset,
     };
+    use num_bigint::BigInt;
+    use num_traits::Zero;
 
-    fn parse_nil(input: &str) -> IResult<&str, Atom> {
+    fn parse_nil(input: &str) -> ReadResult<'_, Atom> {
         value(Atom::Nil, tag("nil"))(input)
     }
 
-    fn parse_symbol_str(input: &str) -> IResult<&str, &str> {
+    fn parse_symbol_str(input: &str) -> ReadResult<'_, &str> {
         fn is_valid_first(c: char) -> bool {
             c.is_alphabetic()
                 || c == '_'
@@ -30,147 +38,416 @@ mod atom {
                 || c == '!'
                 || c == '&'
                 || c == '|'
+                || c == '<'
+                || c == '>'
         }
         let first = verify(anychar, |&c| is_valid_first(c));
-        let rest = verify(anychar, |&c| is_valid_first(c) || c.is_numeric());
-        recognize(pair(first, many0(rest)))(input)
+        let rest = verify(anychar, |&c| {
+            is_valid_first(c) || c.is_numeric() || c == '.'
+        });
+        alt((
+            tag("..."),
+            tag(language::OPTIONAL),
+            recognize(pair(first, many0(rest))),
+        ))(input)
     }
 
     #[test]
     fn test_parse_string() {
-        use nom::{error::Error, error::ErrorKind, Err};
+        use super::error::{ReadError, ReadErrorKind};
+        use nom::{error::ErrorKind, Err};
 
         assert_eq!(parse_symbol_str("foo"), Ok(("", "foo")));
         assert_eq!(parse_symbol_str("foo-bar?"), Ok(("", "foo-bar?")));
+        assert_eq!(parse_symbol_str("=>"), Ok(("", "=>")));
+        assert_eq!(parse_symbol_str("<="), Ok(("", "<=")));
+        assert_eq!(parse_symbol_str("..."), Ok(("", "...")));
+        assert_eq!(parse_symbol_str("#!optional"), Ok(("", "#!optional")));
 
         assert_eq!(
             parse_symbol_str(""),
-            Err(Err::Error(Error {
+            Err(Err::Error(ReadError {
                 input: "",
-                code: ErrorKind::Eof,
+                kind: ReadErrorKind::Nom(ErrorKind::Eof),
             }))
         );
 
         assert_eq!(
             parse_symbol_str("42x"),
-            Err(Err::Error(Error {
+            Err(Err::Error(ReadError {
                 input: "42x",
-                code: ErrorKind::Verify,
+                kind: ReadErrorKind::Nom(ErrorKind::Verify),
             }))
         );
 
         assert_eq!(
-            parse_symbol_str(">"),
-            Err(Err::Error(Error {
-                input: ">",
-                code: ErrorKind::Verify,
+            parse_symbol_str(")"),
+            Err(Err::Error(ReadError {
+                input: ")",
+                kind: ReadErrorKind::Nom(ErrorKind::Verify),
             }))
         );
     }
 
-    fn parse_symbol(input: &str) -> IResult<&str, Atom> {
+    fn parse_symbol(input: &str) -> ReadResult<'_, Atom> {
         map(parse_symbol_str, |s| Atom::Symbol(s.to_string()))(input)
     }
 
-    fn parse_string(input: &str) -> IResult<&str, Atom> {
+    fn parse_string(input: &str) -> ReadResult<'_, Atom> {
         map(string::parse_string, Atom::String)(input)
     }
 
-    fn parse_i64(input: &str) -> IResult<&str, Atom> {
-        map(i64, Atom::I64)(input)
+    /// Parses an integer, which is stored as a big integer if it doesn't fit in an i64
+    fn parse_integer(input: &str) -> ReadResult<'_, Atom> {
+        map(
+            recognize(pair(opt(one_of("+-")), digit1)),
+            |digits: &str| match digits.parse::<i64>() {
+                Ok(i) => Atom::I64(i),
+                Err(_) => Atom::BigInt(digits.parse().expect("invalid integer")),
+            },
+        )(input)
+    }
+
+    /// Parses a fraction, which is stored as an integer if its denominator divides its numerator
+    fn parse_rational(input: &str) -> ReadResult<'_, Atom> {
+        let (rest, (numerator, denominator)) = separated_pair(
+            recognize(pair(opt(one_of("+-")), digit1)),
+            char('/'),
+            digit1,
+        )(input)?;
+        let denominator_start = &input[input.offset(denominator)..];
+        let denominator: BigInt = denominator.parse().expect("invalid denominator");
+        if denominator.is_zero() {
+            return Err(Err::Failure(ReadError::new(
+                denominator_start,
+                ReadErrorKind::ZeroDenominator,
+            )));
+        }
+        let numerator = numerator.parse().expect("invalid numerator");
+        Ok((rest, Number::rational(numerator, denominator).into_atom()))
+    }
+
+    /// Parses a float, which must contain a decimal point or an exponent to distinguish it from
+    /// an integer
+    fn parse_f64(input: &str) -> ReadResult<'_, Atom> {
+        let mantissa = alt((
+            recognize(pair(digit1, opt(pair(char('.'), digit0)))),
+            recognize(pair(char('.'), digit1)),
+        ));
+        let exponent = tuple((one_of("eE"), opt(one_of("+-")), digit1));
+        let decimal = map_opt(
+            recognize(tuple((opt(one_of("+-")), mantissa, opt(exponent)))),
+            |digits: &str| {
+                if digits.contains(['.', 'e', 'E']) {
+                    digits.parse().ok()
+                } else {
+                    None
+                }
+            },
+        );
+        map(
+            alt((
+                value(f64::INFINITY, tag("+inf.0")),
+                value(f64::NEG_INFINITY, tag("-inf.0")),
+                value(f64::NAN, alt((tag("+nan.0"), tag("-nan.0")))),
+                decimal,
+            )),
+            Atom::F64,
+        )(input)
+    }
+
+    fn parse_char_name(input: &str) -> ReadResult<'_, char> {
+        language::CHAR_NAMES
+            .iter()
+            .find_map(|&(name, c)| input.strip_prefix(name).map(|rest| (rest, c)))
+            .ok_or_else(|| Err::Error(ReadError::new(input, ReadErrorKind::Nom(ErrorKind::Tag))))
+    }
+
+    /// Parses a character code in hexadecimal, as in `#\x41`
+    fn parse_char_code(input: &str) -> ReadResult<'_, char> {
+        preceded(
+            char('x'),
+            map_opt(hex_digit1, |digits| {
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+            }),
+        )(input)
+    }
+
+    /// Parses a character literal, which must be a single character, a character name or a
+    /// character code
+    fn parse_char(input: &str) -> ReadResult<'_, Atom> {
+        let (input, _) = tag(language::CHAR_PREFIX)(input)?;
+        let (rest, c) = alt((parse_char_name, parse_char_code, anychar))(input)?;
+        match parse_delimiter(rest) {
+            Ok(_) => Ok((rest, Atom::Char(c))),
+            Err(_) => {
+                // the first character may be a delimiter itself, as in `#\(`
+                let first_len = input.chars().next().map_or(0, char::len_utf8);
+                let end = input[first_len..]
+                    .find(is_delimiter)
+                    .map_or(input.len(), |index| first_len + index);
+                Err(Err::Failure(ReadError::new(
+                    input,
+                    ReadErrorKind::UnknownCharName(&input[..end]),
+                )))
+            }
+        }
+    }
+
+    fn is_delimiter(c: char) -> bool {
+        c.is_whitespace() || "()\";'`,".contains(c)
     }
 
-    fn parse_bool(input: &str) -> IResult<&str, Atom> {
+    /// Checks without consuming it that the input continues with something which may end a
+    /// token, so that a literal isn't read as a prefix of a longer token
+    fn parse_delimiter(input: &str) -> ReadResult<'_, ()> {
+        let delimiter = verify(anychar, |&c| is_delimiter(c));
+        value((), peek(alt((eof, recognize(delimiter)))))(input)
+    }
+
+    fn parse_number(input: &str) -> ReadResult<'_, Atom> {
+        terminated(
+            alt((parse_f64, parse_rational, parse_integer)),
+            parse_delimiter,
+        )(input)
+    }
+
+    fn parse_bool(input: &str) -> ReadResult<'_, Atom> {
         alt((
             value(Atom::Bool(true), tag("true")),
             value(Atom::Bool(false), tag("false")),
         ))(input)
     }
 
-    pub fn parse_atom(input: &str) -> IResult<&str, Atom> {
-        alt((parse_bool, parse_i64, parse_nil, parse_symbol, parse_string))(input)
+    pub fn parse_atom(input: &str) -> ReadResult<'_, Atom> {
+        alt((
+            parse_bool,
+            parse_char,
+            parse_number,
+            parse_nil,
+            parse_symbol,
+            parse_string,
+        ))(input)
     }
 }
 
 mod list {
-    use super::value;
-    use crate::{list, value::Value};
+    use super::{
+        error::{ReadError, ReadErrorKind, ReadResult},
+        space, value, SyntaxKind,
+    };
     use nom::{
-        bytes::complete::tag,
-        combinator::map,
-        sequence::{delimited, pair, preceded},
-        IResult,
+        character::complete::char,
+        combinator::{cut, opt},
+        sequence::preceded,
+        Err,
     };
 
-    pub fn parse_list_with_end(input: &str) -> IResult<&str, Value> {
-        map(
-            delimited(
-                tag("("),
-                pair(value::parse_values, preceded(tag("."), value::parse_value)),
-                tag(")"),
-            ),
-            |(values, end)| list::from_vec_with_end(values, end),
-        )(input)
-    }
-
-    pub fn parse_list(input: &str) -> IResult<&str, Value> {
-        map(
-            delimited(tag("("), value::parse_values, tag(")")),
-            list::from_vec,
-        )(input)
+    pub fn parse_list(input: &str) -> ReadResult<'_, SyntaxKind<'_>> {
+        let (rest, _) = char('(')(input)?;
+        let (rest, values) = value::parse_values(rest)?;
+        let (rest, ()) = space::parse_space(rest)?;
+        let (rest, end) = opt(preceded(char('.'), cut(value::parse_value)))(rest)?;
+        match char::<_, ReadError>(')')(rest) {
+            Ok((rest, _)) => Ok((rest, SyntaxKind::List(values, end.map(Box::new)))),
+            Err(_) => Err(Err::Failure(ReadError::new(
+                rest,
+                ReadErrorKind::UnclosedList { open: input },
+            ))),
+        }
     }
 }
 
 mod quote {
-    use super::value;
-    use crate::{language, value::Value};
-    use nom::{bytes::complete::tag, combinator::map, sequence::preceded, IResult};
+    use super::{error::ReadResult, value, SyntaxKind};
+    use crate::language::Keyword;
+    use nom::{
+        branch::alt,
+        bytes::complete::tag,
+        combinator::{cut, map, value as constant},
+        sequence::pair,
+    };
 
-    pub fn parse_quote(input: &str) -> IResult<&str, Value> {
-        map(
-            preceded(tag("'"), value::parse_value),
-            language::quote_value,
-        )(input)
+    pub fn parse_quote(input: &str) -> ReadResult<'_, SyntaxKind<'_>> {
+        let prefix = alt((
+            constant(Keyword::Quote, tag("'")),
+            constant(Keyword::Quasiquote, tag("`")),
+            constant(Keyword::UnquoteSplicing, tag(",@")),
+            constant(Keyword::Unquote, tag(",")),
+        ));
+        map(pair(prefix, cut(value::parse_value)), |(keyword, value)| {
+            SyntaxKind::Quote(keyword, Box::new(value))
+        })(input)
     }
 }
 
 mod value {
-    use super::{atom, list, quote};
-    use crate::value::Value;
+    use super::{atom, error::ReadResult, list, quote, space, Syntax, SyntaxKind};
     use nom::{
-        branch::alt, character::complete::multispace0, combinator::map, multi::many0,
-        sequence::delimited, IResult,
+        branch::alt,
+        combinator::{consumed, map},
+        multi::many0,
+        sequence::delimited,
     };
 
-    fn parse_value_no_space(input: &str) -> IResult<&str, Value> {
-        alt((
-            map(atom::parse_atom, Value::Atom),
-            list::parse_list_with_end,
-            list::parse_list,
-            quote::parse_quote,
-        ))(input)
+    fn parse_value_no_space(input: &str) -> ReadResult<'_, Syntax<'_>> {
+        map(
+            consumed(alt((
+                map(atom::parse_atom, SyntaxKind::Atom),
+                list::parse_list,
+                quote::parse_quote,
+            ))),
+            |(text, kind)| Syntax { text, kind },
+        )(input)
     }
 
-    pub fn parse_value(input: &str) -> IResult<&str, Value> {
-        delimited(multispace0, parse_value_no_space, multispace0)(input)
+    pub fn parse_value(input: &str) -> ReadResult<'_, Syntax<'_>> {
+        delimited(space::parse_space, parse_value_no_space, space::parse_space)(input)
     }
 
-    pub fn parse_values(input: &str) -> IResult<&str, Vec<Value>> {
+    pub fn parse_values(input: &str) -> ReadResult<'_, Vec<Syntax<'_>>> {
         many0(parse_value)(input)
     }
+
+    pub fn skip_space(input: &str) -> &str {
+        space::parse_space(input)
+            .map(|(rest, ())| rest)
+            .unwrap_or(input)
+    }
 }
 
-use crate::value::Value;
-use nom::{combinator::all_consuming, Finish};
+use crate::{
+    language::{self, Keyword},
+    source::{Source, SourceMap, Span},
+    value::{Atom, Value},
+};
+use nom::{Err, Offset};
+use std::rc::Rc;
+
+pub use error::{ParseError, SyntaxError};
+
+/// A parsed datum along with the text it was parsed from
+struct Syntax<'a> {
+    text: &'a str,
+    kind: SyntaxKind<'a>,
+}
+
+enum SyntaxKind<'a> {
+    Atom(Atom),
+    List(Vec<Syntax<'a>>, Option<Box<Syntax<'a>>>),
+    /// A datum preceded by one of the abbreviations `'`, `` ` ``, `,` or `,@`
+    Quote(Keyword, Box<Syntax<'a>>),
+}
+
+impl<'a> Syntax<'a> {
+    fn into_value(self) -> Value {
+        match self.kind {
+            SyntaxKind::Atom(atom) => Value::Atom(atom),
+            SyntaxKind::List(values, end) => crate::list::from_vec_with_end(
+                values.into_iter().map(Syntax::into_value).collect(),
+                end.map(|end| end.into_value()).unwrap_or_else(Value::nil),
+            ),
+            SyntaxKind::Quote(keyword, value) => {
+                language::prefix_form(keyword, Rc::new(value.into_value()))
+            }
+        }
+    }
+
+    /// Converts to a value, recording the span of each datum in `source_map`
+    fn into_spanned_value(self, source: &Rc<Source>, source_map: &mut SourceMap) -> Rc<Value> {
+        let start = source.text.offset(self.text);
+        let span = Span {
+            source: source.clone(),
+            start,
+            end: start + self.text.len(),
+        };
+        let value = match self.kind {
+            SyntaxKind::Atom(atom) => Rc::new(Value::Atom(atom)),
+            SyntaxKind::List(values, end) => {
+                let mut list = match end {
+                    Some(end) => end.into_spanned_value(source, source_map),
+                    None => Rc::new(Value::nil()),
+                };
+                let values = values
+                    .into_iter()
+                    .map(|value| value.into_spanned_value(source, source_map))
+                    .collect::<Vec<_>>();
+                for value in values.into_iter().rev() {
+                    list = Rc::new(Value::pair(value, list));
+                }
+                list
+            }
+            SyntaxKind::Quote(keyword, value) => {
+                let value = value.into_spanned_value(source, source_map);
+                Rc::new(language::prefix_form(keyword, value))
+            }
+        };
+        source_map.insert(&value, span);
+        value
+    }
+}
+
+/// Where to resume parsing after a syntax error: the next line which starts with an opening
+/// parenthesis, or just past the offending character if the error occurred at the very start of
+/// a top-level datum
+fn recovery_point<'a>(datum_start: &'a str, error_at: &'a str) -> &'a str {
+    if datum_start.offset(error_at) == 0 {
+        let mut chars = error_at.chars();
+        chars.next();
+        return chars.as_str();
+    }
+    match error_at.find("\n(") {
+        Some(index) => &error_at[index + 1..],
+        None => &error_at[error_at.len()..],
+    }
+}
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ParseError;
+fn parse_syntax(source: &Rc<Source>) -> Result<Vec<Syntax<'_>>, ParseError> {
+    let mut input = source.text.as_str();
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    loop {
+        input = value::skip_space(input);
+        if input.is_empty() {
+            break;
+        }
+        match value::parse_value(input) {
+            Ok((rest, value)) => {
+                values.push(value);
+                input = rest;
+            }
+            Err(Err::Error(read_error)) | Err(Err::Failure(read_error)) => {
+                input = recovery_point(input, read_error.input);
+                errors.push(SyntaxError::from_read_error(read_error, source));
+            }
+            Err(Err::Incomplete(_)) => unreachable!("only complete parsers are used"),
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(ParseError { errors })
+    }
+}
 
 pub fn parse(input: &str) -> Result<Vec<Value>, ParseError> {
-    all_consuming(value::parse_values)(input)
-        .finish()
-        .map_err(|_| ParseError)
-        .map(|(_, output)| output)
+    let source = Source::new("<input>", input);
+    parse_syntax(&source).map(|syntax| syntax.into_iter().map(Syntax::into_value).collect())
+}
+
+/// Parses the text of `source`, recording the location of each parsed value in `source_map`
+pub fn parse_source(
+    source: &Rc<Source>,
+    source_map: &mut SourceMap,
+) -> Result<Vec<Rc<Value>>, ParseError> {
+    parse_syntax(source).map(|syntax| {
+        syntax
+            .into_iter()
+            .map(|syntax| syntax.into_spanned_value(source, source_map))
+            .collect()
+    })
 }
 
 pub fn parse_to_list(input: &str) -> Result<Value, ParseError> {
@@ -179,11 +456,15 @@ pub fn parse_to_list(input: &str) -> Result<Value, ParseError> {
 
 #[cfg(test)]
 mod test {
-    use super::parse;
+    use super::{parse, parse_source};
     use crate::{
         language, list,
+        number::Number,
+        source::{Source, SourceMap},
         value::{Atom, Value},
     };
+    use num_bigint::BigInt;
+    use std::rc::Rc;
 
     #[test]
     fn empty_text() {
@@ -256,6 +537,25 @@ mod test {
         assert_eq!(parse(string), Ok(expected_result));
     }
 
+    #[test]
+    fn quasiquoted_list() {
+        let string = r#"
+            `(foo ,bar ,@baz)
+        "#;
+        let expected_result = vec![list::from_vec(vec![
+            Value::symbol("quasiquote"),
+            list::from_vec(vec![
+                Value::symbol("foo"),
+                list::from_vec(vec![Value::symbol("unquote"), Value::symbol("bar")]),
+                list::from_vec(vec![
+                    Value::symbol("unquote-splicing"),
+                    Value::symbol("baz"),
+                ]),
+            ]),
+        ])];
+        assert_eq!(parse(string), Ok(expected_result));
+    }
+
     #[test]
     fn boolean_expression() {
         let string = r#"
@@ -301,6 +601,149 @@ mod test {
         );
     }
 
+    #[test]
+    fn round_trip_big_integers() {
+        round_trip_helper(
+            r#"
+            (123456789012345678901234567890 -9223372036854775809 9223372036854775807)
+        "#,
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(
+            parse("2.75 -1e10 2.5E-3 .5 1. +inf.0 -inf.0"),
+            Ok(vec![
+                Value::f64(2.75),
+                Value::f64(-1e10),
+                Value::f64(2.5e-3),
+                Value::f64(0.5),
+                Value::f64(1.0),
+                Value::f64(f64::INFINITY),
+                Value::f64(f64::NEG_INFINITY),
+            ])
+        );
+        assert_eq!(parse("+nan.0"), Ok(vec![Value::f64(f64::NAN)]));
+        assert_eq!(
+            parse("(1.5)'(2.)"),
+            Ok(vec![
+                list::from_vec(vec![Value::f64(1.5)]),
+                list::from_vec(vec![
+                    Value::symbol("quote"),
+                    list::from_vec(vec![Value::f64(2.0)]),
+                ]),
+            ])
+        );
+        assert!(parse("1.5.3").is_err());
+        assert!(parse("1e5x").is_err());
+        assert!(parse("(12abc)").is_err());
+        assert_eq!(
+            parse("(a . b)"),
+            Ok(vec![Value::pair(
+                Rc::new(Value::symbol("a")),
+                Rc::new(Value::symbol("b"))
+            )])
+        );
+    }
+
+    #[test]
+    fn round_trip_strings() {
+        round_trip_helper(r#"("a\"b" "x\\y" "line\nbreak" "")"#);
+        assert_eq!(
+            parse(r#"("a\"b" "x\\y" "line\nbreak")"#).unwrap()[0].to_string(),
+            r#"("a\"b" "x\\y" "line\nbreak")"#
+        );
+    }
+
+    #[test]
+    fn round_trip_floats() {
+        round_trip_helper("(2.75 -1e10 1e20 1e-7 0.1 -0.0 100.0 +inf.0 -inf.0 +nan.0)");
+        assert_eq!(
+            parse("(1.0 1e20 0.1 +nan.0)").unwrap()[0].to_string(),
+            "(1.0 1e20 0.1 +nan.0)"
+        );
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(
+            parse("+42 -42 9223372036854775808"),
+            Ok(vec![
+                Value::i64(42),
+                Value::i64(-42),
+                Value::Atom(Atom::BigInt(BigInt::from(i64::MAX) + 1)),
+            ])
+        );
+        assert_eq!(
+            parse("- +"),
+            Ok(vec![Value::symbol("-"), Value::symbol("+")])
+        );
+    }
+
+    #[test]
+    fn chars() {
+        assert_eq!(
+            parse(r#"#\a #\space #\newline #\tab #\x41 #\x #\( #\λ"#),
+            Ok(vec![
+                Value::char('a'),
+                Value::char(' '),
+                Value::char('\n'),
+                Value::char('\t'),
+                Value::char('A'),
+                Value::char('x'),
+                Value::char('('),
+                Value::char('λ'),
+            ])
+        );
+        round_trip_helper(r#"(#\a #\space #\newline #\nul #\x7f #\) #\")"#);
+        assert_eq!(
+            parse(r#"(#\a(#\())"#),
+            Ok(vec![list::from_vec(vec![
+                Value::char('a'),
+                list::from_vec(vec![Value::char('(')]),
+            ])])
+        );
+        assert_eq!(
+            error_messages(r#"(#\Space #\a)"#),
+            vec!["3: unknown character name: Space"]
+        );
+        assert_eq!(
+            error_messages(r#"#\return"#),
+            vec!["2: unknown character name: return"]
+        );
+        assert_eq!(
+            error_messages(r#"(list #\abc)"#),
+            vec!["8: unknown character name: abc"]
+        );
+        assert_eq!(
+            error_messages(r#"(list #\x41z)"#),
+            vec!["8: unknown character name: x41z"]
+        );
+    }
+
+    #[test]
+    fn rationals() {
+        assert_eq!(
+            parse("1/3 -2/4 6/3 +0/5").unwrap(),
+            vec![
+                Number::rational(BigInt::from(1), BigInt::from(3)).into_value(),
+                Number::rational(BigInt::from(-1), BigInt::from(2)).into_value(),
+                Value::i64(2),
+                Value::i64(0),
+            ]
+        );
+        round_trip_helper("(1/3 -1/2 100000000000000000000/3)");
+        assert_eq!(
+            error_messages("(foo 1/0)"),
+            vec!["7: zero denominator in fraction"]
+        );
+        assert_eq!(
+            error_messages("-5/00"),
+            vec!["3: zero denominator in fraction"]
+        );
+    }
+
     #[test]
     fn round_trip_quote() {
         round_trip_helper(
@@ -318,4 +761,104 @@ mod test {
         "#,
         );
     }
+
+    #[test]
+    fn error_location() {
+        let source = Source::new("test.lisp", "(foo\n  bar)\n  )\n");
+        let error = parse_source(&source, &mut SourceMap::new()).unwrap_err();
+        assert_eq!(error.errors.len(), 1);
+        assert_eq!(error.errors[0].span.line_col(), (3, 3));
+        assert_eq!(
+            format!("{}", error),
+            "test.lisp:3:3: unexpected ')'\n  |\n3 |   )\n  |   ^"
+        );
+    }
+
+    #[test]
+    fn spans() {
+        let source = Source::new("test.lisp", "(foo\n  'bar)");
+        let mut source_map = SourceMap::new();
+        let values = parse_source(&source, &mut source_map).unwrap();
+        let (foo, rest) = list::split_head(&*values[0]).unwrap();
+        let quoted_bar = list::head(&rest).unwrap();
+        let span_line_col = |value| source_map.get(value).map(|span| span.line_col());
+        assert_eq!(span_line_col(&values[0]), Some((1, 1)));
+        assert_eq!(span_line_col(&foo), Some((1, 2)));
+        assert_eq!(span_line_col(&quoted_bar), Some((2, 3)));
+    }
+
+    fn error_messages(string: &str) -> Vec<String> {
+        parse(string)
+            .unwrap_err()
+            .errors
+            .iter()
+            .map(|error| format!("{}: {}", error.offset(), error.kind))
+            .collect()
+    }
+
+    #[test]
+    fn unclosed_list() {
+        assert_eq!(
+            error_messages("\n\n    (foo\n  (bar)"),
+            vec!["18: expected closing ')' for list opened at 3:5, found end of input"]
+        );
+        assert_eq!(
+            error_messages("(foo ]"),
+            vec!["5: expected closing ')' for list opened at 1:1, found ']'"]
+        );
+    }
+
+    #[test]
+    fn bad_strings() {
+        assert_eq!(
+            error_messages(r#"(foo "bar)"#),
+            vec!["5: unterminated string literal"]
+        );
+        assert_eq!(
+            error_messages(r#"(foo "b\xr")"#),
+            vec!["8: invalid escape \\x"]
+        );
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let string = r#"(define a "x\q")
+(define b 2))
+(define c (+ 1 2)
+"#;
+        assert_eq!(
+            error_messages(string),
+            vec![
+                "13: invalid escape \\q",
+                "29: unexpected ')'",
+                "49: expected closing ')' for list opened at 3:1, found end of input",
+            ]
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let string = r#"
+            ; a line comment
+            (foo ; another line comment
+                #| a block comment #| with a nested block comment |# |#
+                bar #;(baz qux)
+                #;quux ; a commented-out datum
+            )
+            ( #| an empty list |# )
+        "#;
+        let expected_result = vec![
+            list::from_vec(vec![Value::symbol("foo"), Value::symbol("bar")]),
+            Value::nil(),
+        ];
+        assert_eq!(parse(string), Ok(expected_result));
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        assert_eq!(
+            error_messages("(foo) #| bar #| baz |#"),
+            vec!["6: unterminated block comment"]
+        );
+    }
 }
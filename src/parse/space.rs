@@ -0,0 +1,81 @@
+use super::{
+    error::{ReadError, ReadErrorKind, ReadResult},
+    value,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till},
+    character::complete::multispace1,
+    combinator::{cut, value},
+    multi::many0,
+    sequence::{pair, preceded},
+    Err,
+};
+
+/// A `;` comment, up to but not including the end of the line
+fn parse_line_comment(input: &str) -> ReadResult<'_, ()> {
+    value((), pair(tag(";"), take_till(|c| c == '\n')))(input)
+}
+
+/// A `#| ... |#` comment, which may contain nested block comments
+fn parse_block_comment(input: &str) -> ReadResult<'_, ()> {
+    let (mut rest, _) = tag("#|")(input)?;
+    let mut depth = 1;
+    while depth > 0 {
+        if let Some(after) = rest.strip_prefix("#|") {
+            depth += 1;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("|#") {
+            depth -= 1;
+            rest = after;
+        } else {
+            let mut chars = rest.chars();
+            if chars.next().is_none() {
+                return Err(Err::Failure(ReadError::new(
+                    rest,
+                    ReadErrorKind::UnterminatedBlockComment { open: input },
+                )));
+            }
+            rest = chars.as_str();
+        }
+    }
+    Ok((rest, ()))
+}
+
+/// A `#;` comment, which comments out the datum following it
+fn parse_datum_comment(input: &str) -> ReadResult<'_, ()> {
+    value((), preceded(tag("#;"), cut(value::parse_value)))(input)
+}
+
+/// Whitespace and comments
+pub fn parse_space(input: &str) -> ReadResult<'_, ()> {
+    value(
+        (),
+        many0(alt((
+            value((), multispace1),
+            parse_line_comment,
+            parse_block_comment,
+            parse_datum_comment,
+        ))),
+    )(input)
+}
+
+#[test]
+fn test_parse_space() {
+    assert_eq!(parse_space(""), Ok(("", ())));
+    assert_eq!(parse_space("  foo"), Ok(("foo", ())));
+    assert_eq!(parse_space("; comment\n  foo"), Ok(("foo", ())));
+    assert_eq!(parse_space("#| a #| b |# c |# foo"), Ok(("foo", ())));
+    assert_eq!(parse_space("#; (a (b c)) foo"), Ok(("foo", ())));
+    assert_eq!(parse_space("#;#;a b foo"), Ok(("foo", ())));
+
+    assert_eq!(
+        parse_space("#| a #| b |# c"),
+        Err(Err::Failure(ReadError {
+            input: "",
+            kind: ReadErrorKind::UnterminatedBlockComment {
+                open: "#| a #| b |# c"
+            },
+        }))
+    );
+}
@@ -1,25 +1,32 @@
+use super::error::{ReadError, ReadErrorKind, ReadResult};
 use nom::{
     branch::alt,
     bytes::complete::is_not,
-    character::complete::char,
+    character::complete::{anychar, char},
     combinator::{map, value, verify},
+    error::ErrorKind,
     multi::fold_many0,
-    sequence::{delimited, preceded},
-    IResult,
+    sequence::preceded,
+    Err,
 };
 
-fn parse_escaped_char(input: &str) -> IResult<&str, char> {
-    preceded(
-        char('\\'),
-        alt((
-            value('\n', char('n')),
-            value('\\', char('\\')),
-            value('"', char('"')),
-        )),
-    )(input)
+fn parse_escape(input: &str) -> ReadResult<'_, char> {
+    alt::<_, _, ReadError, _>((
+        value('\n', char('n')),
+        value('\\', char('\\')),
+        value('"', char('"')),
+    ))(input)
+    .map_err(|_| match anychar::<_, ReadError>(input) {
+        Ok((_, c)) => Err::Failure(ReadError::new(input, ReadErrorKind::InvalidEscape(c))),
+        Err(_) => Err::Error(ReadError::new(input, ReadErrorKind::Nom(ErrorKind::Eof))),
+    })
+}
+
+fn parse_escaped_char(input: &str) -> ReadResult<'_, char> {
+    preceded(char('\\'), parse_escape)(input)
 }
 
-fn parse_string_literal_contents_non_empty(input: &str) -> IResult<&str, &str> {
+fn parse_string_literal_contents_non_empty(input: &str) -> ReadResult<'_, &str> {
     verify(is_not("\\\""), |s: &str| !s.is_empty())(input)
 }
 
@@ -28,7 +35,7 @@ enum StringContentsFragment<'a> {
     LiteralContentsNonEmpty(&'a str),
 }
 
-fn parse_string_contents_fragment(input: &str) -> IResult<&str, StringContentsFragment> {
+fn parse_string_contents_fragment(input: &str) -> ReadResult<'_, StringContentsFragment<'_>> {
     alt((
         map(parse_escaped_char, StringContentsFragment::EscapedChar),
         map(
@@ -38,7 +45,7 @@ fn parse_string_contents_fragment(input: &str) -> IResult<&str, StringContentsFr
     ))(input)
 }
 
-fn parse_string_contents(input: &str) -> IResult<&str, String> {
+fn parse_string_contents(input: &str) -> ReadResult<'_, String> {
     fold_many0(
         parse_string_contents_fragment,
         String::new,
@@ -52,14 +59,20 @@ fn parse_string_contents(input: &str) -> IResult<&str, String> {
     )(input)
 }
 
-pub fn parse_string(input: &str) -> IResult<&str, String> {
-    delimited(char('"'), parse_string_contents, char('"'))(input)
+pub fn parse_string(input: &str) -> ReadResult<'_, String> {
+    let (rest, _) = char('"')(input)?;
+    let (rest, string) = parse_string_contents(rest)?;
+    match char::<_, ReadError>('"')(rest) {
+        Ok((rest, _)) => Ok((rest, string)),
+        Err(_) => Err(Err::Failure(ReadError::new(
+            rest,
+            ReadErrorKind::UnterminatedString { open: input },
+        ))),
+    }
 }
 
 #[test]
 fn test_parse_string() {
-    use nom::{error::Error, error::ErrorKind, Err};
-
     assert_eq!(parse_string(r#""""#), Ok(("", "".to_string())));
     assert_eq!(parse_string(r#""foo""#), Ok(("", "foo".to_string())));
     assert_eq!(
@@ -69,25 +82,41 @@ fn test_parse_string() {
 
     assert_eq!(
         parse_string(r#""#),
-        Err(Err::Error(Error {
+        Err(Err::Error(ReadError {
             input: "",
-            code: ErrorKind::Char,
+            kind: ReadErrorKind::Nom(ErrorKind::Char),
         }))
     );
 
     assert_eq!(
         parse_string(r#"foo"#),
-        Err(Err::Error(Error {
+        Err(Err::Error(ReadError {
             input: "foo",
-            code: ErrorKind::Char,
+            kind: ReadErrorKind::Nom(ErrorKind::Char),
         }))
     );
 
     assert_eq!(
         parse_string(r#"foo\xbar"#),
-        Err(Err::Error(Error {
+        Err(Err::Error(ReadError {
             input: r#"foo\xbar"#,
-            code: ErrorKind::Char,
+            kind: ReadErrorKind::Nom(ErrorKind::Char),
+        }))
+    );
+
+    assert_eq!(
+        parse_string(r#""foo\xbar""#),
+        Err(Err::Failure(ReadError {
+            input: r#"xbar""#,
+            kind: ReadErrorKind::InvalidEscape('x'),
+        }))
+    );
+
+    assert_eq!(
+        parse_string(r#""foo"#),
+        Err(Err::Failure(ReadError {
+            input: "",
+            kind: ReadErrorKind::UnterminatedString { open: r#""foo"# },
         }))
     );
 }
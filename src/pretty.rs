@@ -1,5 +1,5 @@
 mod atom {
-    use crate::value::Atom;
+    use crate::{language, value::Atom};
     use std::fmt;
 
     fn nil(f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -10,50 +10,110 @@ mod atom {
         write!(f, "{}", s)
     }
 
+    /// Prints strings with the escapes accepted by the parser, so that they can be read back
     fn string(s: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "\"{}\"", s)
+        write!(f, "\"")?;
+        for c in s.chars() {
+            match c {
+                '\\' => write!(f, "\\\\")?,
+                '"' => write!(f, "\\\"")?,
+                '\n' => write!(f, "\\n")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
     }
 
     fn i64(i: i64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", i)
     }
 
+    /// Prints floats so that they are read back as the same float, always including a decimal
+    /// point or exponent to distinguish them from integers
+    fn f64(x: f64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if x.is_nan() {
+            write!(f, "+nan.0")
+        } else if x.is_infinite() {
+            write!(f, "{}inf.0", if x > 0.0 { "+" } else { "-" })
+        } else {
+            write!(f, "{:?}", x)
+        }
+    }
+
     fn bool(b: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", b)
     }
 
+    /// Prints named and control characters so that they can be read back
+    fn char(c: char, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = language::CHAR_PREFIX;
+        match language::CHAR_NAMES.iter().find(|&&(_, named)| named == c) {
+            Some((name, _)) => write!(f, "{}{}", prefix, name),
+            None if c.is_control() => write!(f, "{}x{:x}", prefix, c as u32),
+            None => write!(f, "{}{}", prefix, c),
+        }
+    }
+
     pub fn fmt_atom(atom: &Atom, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match atom {
             Atom::Nil => nil(f),
             Atom::Symbol(s) => symbol(s.as_ref(), f),
             Atom::String(s) => string(s.as_ref(), f),
             Atom::I64(i) => i64(*i, f),
+            Atom::BigInt(i) => write!(f, "{}", i),
+            Atom::Rational(r) => write!(f, "{}", r),
+            Atom::F64(x) => f64(*x, f),
             Atom::Bool(b) => bool(*b, f),
+            Atom::Char(c) => char(*c, f),
+        }
+    }
+}
+
+mod function {
+    use crate::value::Function;
+    use std::fmt;
+
+    pub fn fmt_function(function: &Function, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match function {
+            Function::BuiltIn(built_in) => write!(f, "#<built-in {}>", built_in.as_str()),
+            Function::Lambda(_) => write!(f, "#<lambda>"),
+            Function::Macro(_) => write!(f, "#<macro>"),
+            Function::SyntaxRules(_) => write!(f, "#<syntax-rules>"),
         }
     }
 }
 
 mod pair {
-    use super::{atom::fmt_atom, value::fmt_value};
-    use crate::value::{Pair, Value};
+    use super::value::fmt_value;
+    use crate::{
+        list::CycleCheck,
+        value::{Pair, Value},
+    };
     use std::fmt;
 
+    /// Prints the elements of a list in a loop rather than recursing on its cdr. A circular list
+    /// is printed until the cycle is detected, followed by `...`.
     pub fn inner(pair: &Pair, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt_value(&pair.0, f)?;
-        match &*pair.1 {
-            Value::Atom(atom) => {
-                if atom.is_nil() {
-                    Ok(())
-                } else {
+        fmt_value(&pair.car(), f)?;
+        let mut tail = pair.cdr();
+        let mut cycle_check = CycleCheck::new(&tail);
+        loop {
+            let next = match &*tail {
+                Value::Atom(atom) if atom.is_nil() => return Ok(()),
+                Value::Pair(pair) => {
+                    write!(f, " ")?;
+                    fmt_value(&pair.car(), f)?;
+                    pair.cdr()
+                }
+                end => {
                     write!(f, " . ")?;
-                    fmt_atom(atom, f)
+                    return fmt_value(end, f);
                 }
+            };
+            tail = next;
+            if cycle_check.is_cycle(&tail) {
+                return write!(f, " ...");
             }
-            Value::Pair(pair) => {
-                write!(f, " ")?;
-                inner(pair, f)
-            }
-            Value::Function(_) => todo!(),
         }
     }
 
@@ -65,7 +125,7 @@ mod pair {
 }
 
 mod value {
-    use super::{atom, pair};
+    use super::{atom, function, pair};
     use crate::value::Value;
     use std::fmt;
 
@@ -73,7 +133,8 @@ mod value {
         match value {
             Value::Atom(atom) => atom::fmt_atom(atom, f),
             Value::Pair(pair) => pair::fmt_pair(pair, f),
-            Value::Function(_) => todo!(),
+            Value::Function(function) => function::fmt_function(function, f),
+            Value::Identifier(identifier) => write!(f, "{}", identifier.syntactic_name()),
         }
     }
 }
@@ -92,7 +153,7 @@ pub struct FmtMultiWithNewlines<'a>(pub &'a [Value]);
 impl<'a> fmt::Display for FmtMultiWithNewlines<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for v in self.0 {
-            write!(f, "{}\n", v)?;
+            writeln!(f, "{}", v)?;
         }
         Ok(())
     }
@@ -0,0 +1,224 @@
+use crate::{eval::Runtime, source::Source};
+use rustyline::{error::ReadlineError, DefaultEditor};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = ". ";
+const HISTORY_FILE_NAME: &str = ".lisp1_history";
+
+const HELP: &str = "\
+:load FILE  evaluate the contents of FILE
+:env        list top-level variables and their values
+:help       print this message
+:quit       exit the repl";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Ready for a new entry
+    Ready,
+    /// The input so far is an incomplete form which continues on the next line
+    Incomplete,
+    Quit,
+}
+
+/// Accumulates lines of input into complete entries and evaluates them in a runtime which
+/// persists between entries
+pub struct Repl {
+    runtime: Runtime,
+    buffer: String,
+    entry_count: usize,
+}
+
+impl Repl {
+    pub fn new(runtime: Runtime) -> Self {
+        Self {
+            runtime,
+            buffer: String::new(),
+            entry_count: 0,
+        }
+    }
+
+    /// The text of the entry currently being read (including the most recent line)
+    pub fn buffer(&self) -> &str {
+        self.buffer.as_str()
+    }
+
+    /// Discards any partially entered form
+    pub fn cancel(&mut self) {
+        self.buffer.clear();
+    }
+
+    fn meta_command<W: Write>(&mut self, command: &str, out: &mut W) -> io::Result<Status> {
+        let (name, arg) = match command.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (command, ""),
+        };
+        match name {
+            "quit" | "q" => return Ok(Status::Quit),
+            "help" | "h" => writeln!(out, "{}", HELP)?,
+            "env" => {
+                let mut variables = self.runtime.top_level_variables();
+                variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (name, value) in variables {
+                    writeln!(out, "{} = {}", name, value)?;
+                }
+            }
+            "load" if !arg.is_empty() => match fs::read_to_string(arg) {
+                Ok(text) => {
+                    if let Err(error) = self.runtime.eval_source(&Source::new(arg, text)) {
+                        writeln!(out, "{}", error)?;
+                    }
+                }
+                Err(error) => writeln!(out, "{}: {}", arg, error)?,
+            },
+            "load" => writeln!(out, "usage: :load FILE")?,
+            _ => writeln!(out, "unknown command :{} (try :help)", name)?,
+        }
+        Ok(Status::Ready)
+    }
+
+    /// Handles a line of input. Meta-commands and the results of evaluating complete forms are
+    /// written to `out`.
+    pub fn handle_line<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<Status> {
+        if self.buffer.is_empty() {
+            if let Some(command) = line.trim().strip_prefix(':') {
+                return self.meta_command(command, out);
+            }
+        }
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+        self.entry_count += 1;
+        let source = Source::new(format!("<repl:{}>", self.entry_count), self.buffer.as_str());
+        let values = match self.runtime.parse_source(&source) {
+            Ok(values) => values,
+            Err(error) if error.is_incomplete() => {
+                self.entry_count -= 1;
+                return Ok(Status::Incomplete);
+            }
+            Err(error) => {
+                self.buffer.clear();
+                writeln!(out, "{}", error)?;
+                return Ok(Status::Ready);
+            }
+        };
+        self.buffer.clear();
+        for value in values {
+            match self.runtime.eval(&value) {
+                Ok(Some(result)) => writeln!(out, "{}", result)?,
+                Ok(None) => (),
+                Err(error) => {
+                    writeln!(out, "{}", error)?;
+                    break;
+                }
+            }
+        }
+        Ok(Status::Ready)
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE_NAME))
+}
+
+/// Runs an interactive session on the terminal until the user quits or closes stdin
+pub fn run(runtime: Runtime) -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let history_path = history_path();
+    if let Some(path) = history_path.as_ref() {
+        // there is no history the first time the repl is run
+        let _ = editor.load_history(path);
+    }
+    let mut repl = Repl::new(runtime);
+    let mut stdout = io::stdout();
+    let mut status = Status::Ready;
+    loop {
+        let prompt = match status {
+            Status::Incomplete => CONTINUATION_PROMPT,
+            _ => PROMPT,
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                let was_empty = repl.buffer().is_empty();
+                let entry = if was_empty {
+                    line.clone()
+                } else {
+                    format!("{}{}", repl.buffer(), line)
+                };
+                status = repl.handle_line(line.as_str(), &mut stdout)?;
+                if status != Status::Incomplete && !entry.trim().is_empty() {
+                    editor.add_history_entry(entry.as_str())?;
+                }
+                if status == Status::Quit {
+                    break;
+                }
+            }
+            Err(ReadlineError::Interrupted) => {
+                repl.cancel();
+                status = Status::Ready;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error),
+        }
+    }
+    if let Some(path) = history_path.as_ref() {
+        editor.save_history(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Repl, Status};
+    use crate::eval::Runtime;
+
+    fn run_lines(lines: &[&str]) -> (Vec<Status>, String) {
+        let mut repl = Repl::new(Runtime::new());
+        let mut out = Vec::new();
+        let statuses = lines
+            .iter()
+            .map(|line| repl.handle_line(line, &mut out).unwrap())
+            .collect();
+        (statuses, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn state_persists_between_entries() {
+        let (_, out) = run_lines(&["(define x 40)", "(+ x 2)", "'(a b)"]);
+        assert_eq!(out, "42\n(a b)\n");
+    }
+
+    #[test]
+    fn multi_line_entry() {
+        let (statuses, out) = run_lines(&["(define f (lambda (x)", "  (* x 2)))", "(f", "21)"]);
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Incomplete,
+                Status::Ready,
+                Status::Incomplete,
+                Status::Ready
+            ]
+        );
+        assert_eq!(out, "42\n");
+    }
+
+    #[test]
+    fn errors() {
+        let (statuses, out) = run_lines(&["(+ 1 x)", ")", "(+ 1 2)"]);
+        assert_eq!(statuses, vec![Status::Ready; 3]);
+        assert!(out.starts_with("<repl:1>:1:6: unbound variable: x\n"));
+        assert!(out.contains("<repl:2>:1:1: unexpected ')'\n"));
+        assert!(out.ends_with("3\n"));
+    }
+
+    #[test]
+    fn meta_commands() {
+        let (statuses, out) = run_lines(&["(define b 2)", "(define a 1)", ":env", ":quit"]);
+        assert_eq!(out, "a = 1\nb = 2\n");
+        assert_eq!(statuses[3], Status::Quit);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::value::Value;
+use std::{
+    collections::HashMap,
+    fmt,
+    rc::{Rc, Weak},
+};
+
+/// The text of a program along with the name used to refer to it in diagnostics (usually a
+/// file path).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+}
+
+impl Source {
+    pub fn new<N: Into<String>, T: Into<String>>(name: N, text: T) -> Rc<Self> {
+        Rc::new(Self {
+            name: name.into(),
+            text: text.into(),
+        })
+    }
+
+    /// Returns the 1-based line and column (counted in chars) of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let col = before[line_start..].chars().count() + 1;
+        (line, col)
+    }
+
+    fn line_containing(&self, offset: usize) -> &str {
+        let line_start = self.text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = self.text[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(self.text.len());
+        &self.text[line_start..line_end]
+    }
+}
+
+/// A byte range within a `Source`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub source: Rc<Source>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn line_col(&self) -> (usize, usize) {
+        self.source.line_col(self.start)
+    }
+
+    /// The line of source containing the start of the span, with the span underlined by carets
+    /// on the following line. Spans covering multiple lines are underlined up to the end of
+    /// their first line.
+    pub fn excerpt(&self) -> Excerpt<'_> {
+        Excerpt(self)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, col) = self.line_col();
+        write!(f, "{}:{}:{}", self.source.name, line, col)
+    }
+}
+
+pub struct Excerpt<'a>(&'a Span);
+
+impl<'a> fmt::Display for Excerpt<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.0;
+        let (line, col) = span.line_col();
+        let line_text = span.source.line_containing(span.start);
+        let line_number = line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let underline_len = span.source.text[span.start..span.end.max(span.start)]
+            .lines()
+            .next()
+            .map(|s| s.chars().count())
+            .unwrap_or(0)
+            .max(1);
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, line_text)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(col - 1),
+            "^".repeat(underline_len)
+        )
+    }
+}
+
+/// Associates values produced by the parser with the location in the source they were parsed
+/// from. Values are identified by the address of their `Rc`. Entries hold a `Weak` reference,
+/// which doesn't keep the value alive but does keep its allocation (so the address can't be
+/// reused while the entry exists). Entries for values which have been dropped are pruned as the
+/// map grows.
+#[derive(Default)]
+pub struct SourceMap {
+    spans: HashMap<*const Value, (Weak<Value>, Span)>,
+    /// The number of entries at which to next prune dropped values
+    prune_at: usize,
+}
+
+impl SourceMap {
+    const MIN_PRUNE_AT: usize = 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, value: &Rc<Value>, span: Span) {
+        if self.spans.len() >= self.prune_at {
+            self.spans.retain(|_, (value, _)| value.strong_count() > 0);
+            self.prune_at = (self.spans.len() * 2).max(Self::MIN_PRUNE_AT);
+        }
+        self.spans
+            .insert(Rc::as_ptr(value), (Rc::downgrade(value), span));
+    }
+
+    pub fn get(&self, value: &Rc<Value>) -> Option<&Span> {
+        self.spans
+            .get(&Rc::as_ptr(value))
+            .filter(|(weak, _)| weak.strong_count() > 0)
+            .map(|(_, span)| span)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Source, SourceMap, Span};
+    use crate::value::Value;
+    use std::rc::Rc;
+
+    #[test]
+    fn line_col() {
+        let source = Source::new("test", "(foo\n  bar)\n");
+        assert_eq!(source.line_col(0), (1, 1));
+        assert_eq!(source.line_col(1), (1, 2));
+        assert_eq!(source.line_col(7), (2, 3));
+    }
+
+    #[test]
+    fn excerpt() {
+        let span = Span {
+            source: Source::new("test.lisp", "(define x 1)\n(+ x y)\n"),
+            start: 18,
+            end: 19,
+        };
+        assert_eq!(format!("{}", span), "test.lisp:2:6");
+        assert_eq!(
+            format!("{}", span.excerpt()),
+            "  |\n2 | (+ x y)\n  |      ^"
+        );
+    }
+
+    #[test]
+    fn source_map_does_not_keep_values_alive() {
+        let source = Source::new("test", "x");
+        let span = Span {
+            source: source.clone(),
+            start: 0,
+            end: 1,
+        };
+        let mut source_map = SourceMap::new();
+        let kept = Rc::new(Value::symbol("x"));
+        source_map.insert(&kept, span.clone());
+        for _ in 0..10_000 {
+            let value = Rc::new(Value::symbol("x"));
+            source_map.insert(&value, span.clone());
+            assert_eq!(Rc::strong_count(&value), 1);
+        }
+        // dropped values are pruned, while live ones keep their spans
+        assert!(source_map.spans.len() <= 2 * SourceMap::MIN_PRUNE_AT);
+        assert_eq!(source_map.get(&kept), Some(&span));
+    }
+}
@@ -0,0 +1,560 @@
+use crate::{error::RuntimeErrorKind, eval::Scope, language::Keyword, list, value::Value};
+use std::{collections::HashMap, fmt, rc::Rc};
+
+const DEFAULT_ELLIPSIS: &str = "...";
+const WILDCARD: &str = "_";
+
+/// A symbol introduced by the template of a `syntax-rules` macro. It is bound under a name which
+/// is unique to the expansion that introduced it, so it can't capture identifiers passed to the
+/// macro (or be captured by them). Where the expansion doesn't bind it, it refers to `symbol` in
+/// the scope the macro was defined in.
+#[derive(Clone)]
+pub struct Identifier {
+    pub name: String,
+    pub symbol: Rc<Value>,
+    pub scope: Rc<Scope>,
+}
+
+impl Identifier {
+    /// The name of the symbol this identifier was renamed from, used to recognise keywords
+    pub fn syntactic_name(&self) -> &str {
+        self.symbol
+            .syntactic_name()
+            .expect("identifier renamed from a non-symbol")
+    }
+}
+
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Identifier {}
+
+impl fmt::Debug for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identifier")
+            .field("name", &self.name)
+            .field("symbol", &self.symbol)
+            .finish()
+    }
+}
+
+/// The values matched by a pattern variable. Variables followed by an ellipsis in the pattern
+/// match a sequence of values, one for each repetition.
+#[derive(Clone)]
+enum Binding {
+    One(Rc<Value>),
+    Many(Vec<Binding>),
+}
+
+type Bindings = HashMap<String, Binding>;
+
+/// A macro defined by `syntax-rules`, which rewrites forms matching one of its patterns into the
+/// corresponding template
+pub struct SyntaxRules {
+    ellipsis: String,
+    literals: Vec<String>,
+    rules: Vec<(Rc<Value>, Rc<Value>)>,
+    scope: Rc<Scope>,
+}
+
+impl PartialEq for SyntaxRules {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for SyntaxRules {}
+
+impl fmt::Debug for SyntaxRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyntaxRules")
+            .field("ellipsis", &self.ellipsis)
+            .field("literals", &self.literals)
+            .field("rules", &self.rules)
+            .finish()
+    }
+}
+
+impl SyntaxRules {
+    /// Parses the arguments of a `syntax-rules` form, which are an optional custom ellipsis, a
+    /// list of literals and then the `(pattern template)` rules
+    pub fn new(
+        form: &Rc<Value>,
+        args: &Value,
+        scope: &Rc<Scope>,
+    ) -> Result<Self, RuntimeErrorKind> {
+        let malformed = || RuntimeErrorKind::MalformedSpecialForm {
+            keyword: Keyword::SyntaxRules,
+            form: form.clone(),
+        };
+        let args = list::to_vec(args).ok_or_else(malformed)?;
+        let (ellipsis, args) = match args.split_first() {
+            Some((ellipsis, rest)) if ellipsis.syntactic_name().is_some() => {
+                (ellipsis.syntactic_name().unwrap_or_default(), rest)
+            }
+            _ => (DEFAULT_ELLIPSIS, args.as_slice()),
+        };
+        let (literals, rules) = args.split_first().ok_or_else(malformed)?;
+        let literals = list::to_vec(&**literals)
+            .ok_or_else(malformed)?
+            .iter()
+            .map(|literal| literal.syntactic_name().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(malformed)?;
+        let rules = rules
+            .iter()
+            .map(|rule| match list::to_vec(&**rule).as_deref() {
+                Some([pattern, template]) if matches!(&**pattern, Value::Pair(_)) => {
+                    Some((pattern.clone(), template.clone()))
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(malformed)?;
+        Ok(Self {
+            ellipsis: ellipsis.to_string(),
+            literals,
+            rules,
+            scope: scope.clone(),
+        })
+    }
+
+    fn is_ellipsis(&self, value: &Value) -> bool {
+        value.syntactic_name() == Some(self.ellipsis.as_str())
+    }
+
+    fn is_literal(&self, value: &Value) -> bool {
+        value
+            .syntactic_name()
+            .is_some_and(|name| self.literals.iter().any(|literal| literal == name))
+    }
+
+    /// Rewrites `form` using the first rule whose pattern matches it. `next_alias` numbers the
+    /// identifiers introduced by the expansion so that each is distinct from all others.
+    pub fn expand(
+        &self,
+        form: &Rc<Value>,
+        next_alias: &mut usize,
+    ) -> Result<Rc<Value>, RuntimeErrorKind> {
+        // the keyword at the head of the form is ignored by the pattern
+        let args = match &**form {
+            Value::Pair(pair) => pair.cdr(),
+            _ => return Err(RuntimeErrorKind::NoMatchingSyntaxRule(form.clone())),
+        };
+        for (pattern, template) in self.rules.iter() {
+            let pattern_args = match &**pattern {
+                Value::Pair(pair) => pair.cdr(),
+                _ => continue,
+            };
+            let mut bindings = Bindings::new();
+            if self.match_pattern(&pattern_args, &args, &mut bindings) {
+                let mut expansion = Expansion {
+                    rules: self,
+                    renames: HashMap::new(),
+                    next_alias,
+                };
+                return expansion
+                    .instantiate(template, &bindings, true)
+                    .map(|expansion| strip_data(&expansion))
+                    .ok_or_else(|| RuntimeErrorKind::MalformedSpecialForm {
+                        keyword: Keyword::SyntaxRules,
+                        form: template.clone(),
+                    });
+            }
+        }
+        Err(RuntimeErrorKind::NoMatchingSyntaxRule(form.clone()))
+    }
+
+    /// The names of the variables in a pattern
+    fn pattern_variables(&self, pattern: &Value, variables: &mut Vec<String>) {
+        match pattern {
+            Value::Pair(pair) => {
+                self.pattern_variables(&pair.car(), variables);
+                self.pattern_variables(&pair.cdr(), variables);
+            }
+            _ => {
+                if let Some(name) = pattern.as_symbol() {
+                    if !self.is_ellipsis(pattern)
+                        && !self.is_literal(pattern)
+                        && pattern.syntactic_name() != Some(WILDCARD)
+                    {
+                        variables.push(name.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    fn match_pattern(
+        &self,
+        pattern: &Rc<Value>,
+        form: &Rc<Value>,
+        bindings: &mut Bindings,
+    ) -> bool {
+        if let Some(name) = pattern.as_symbol() {
+            if self.is_literal(pattern) {
+                return form.syntactic_name().is_some()
+                    && form.syntactic_name() == pattern.syntactic_name();
+            }
+            if pattern.syntactic_name() != Some(WILDCARD) {
+                bindings.insert(name.to_string(), Binding::One(form.clone()));
+            }
+            return true;
+        }
+        if !matches!(&**pattern, Value::Pair(_)) {
+            return **pattern == **form;
+        }
+        let (elements, pattern_end) = list::to_vec_with_end(pattern);
+        let (items, form_end) = list::to_vec_with_end(form);
+        let (before, repeated, after) = match elements
+            .iter()
+            .position(|element| self.is_ellipsis(element))
+        {
+            Some(0) => return false,
+            Some(i) => (
+                &elements[..i - 1],
+                Some(&elements[i - 1]),
+                &elements[i + 1..],
+            ),
+            None => (elements.as_slice(), None, &elements[elements.len()..]),
+        };
+        let fixed = before.len() + after.len();
+        // without an ellipsis or a dotted tail in the pattern, the form must have exactly as many
+        // items as the pattern
+        if items.len() < fixed
+            || (repeated.is_none() && pattern_end.is_nil() && items.len() != fixed)
+        {
+            return false;
+        }
+        for (pattern, item) in before.iter().zip(items.iter()) {
+            if !self.match_pattern(pattern, item, bindings) {
+                return false;
+            }
+        }
+        let repeated_count = match repeated {
+            Some(repeated) => {
+                let count = items.len() - fixed;
+                let mut variables = Vec::new();
+                self.pattern_variables(repeated, &mut variables);
+                let mut sequences = vec![Vec::new(); variables.len()];
+                for item in &items[before.len()..before.len() + count] {
+                    let mut item_bindings = Bindings::new();
+                    if !self.match_pattern(repeated, item, &mut item_bindings) {
+                        return false;
+                    }
+                    for (variable, sequence) in variables.iter().zip(sequences.iter_mut()) {
+                        if let Some(binding) = item_bindings.remove(variable) {
+                            sequence.push(binding);
+                        }
+                    }
+                }
+                for (variable, sequence) in variables.into_iter().zip(sequences) {
+                    bindings.insert(variable, Binding::Many(sequence));
+                }
+                count
+            }
+            None => 0,
+        };
+        let after_start = before.len() + repeated_count;
+        for (pattern, item) in after.iter().zip(items[after_start..].iter()) {
+            if !self.match_pattern(pattern, item, bindings) {
+                return false;
+            }
+        }
+        let rest =
+            list::from_rc_vec_with_end(items[after_start + after.len()..].to_vec(), form_end);
+        self.match_pattern(&pattern_end, &rest, bindings)
+    }
+}
+
+/// The state of a single expansion of a macro
+struct Expansion<'a> {
+    rules: &'a SyntaxRules,
+    /// The identifier each symbol in the template has been renamed to, so that every occurrence
+    /// of a symbol in one expansion refers to the same binding
+    renames: HashMap<String, Rc<Value>>,
+    next_alias: &'a mut usize,
+}
+
+impl<'a> Expansion<'a> {
+    fn rename(&mut self, symbol: &Rc<Value>, name: &str) -> Rc<Value> {
+        if let Some(identifier) = self.renames.get(name) {
+            return identifier.clone();
+        }
+        *self.next_alias += 1;
+        let identifier = Rc::new(Value::Identifier(Identifier {
+            name: format!(
+                "{}#{}",
+                symbol.syntactic_name().unwrap_or_default(),
+                self.next_alias
+            ),
+            symbol: symbol.clone(),
+            scope: self.rules.scope.clone(),
+        }));
+        self.renames.insert(name.to_string(), identifier.clone());
+        identifier
+    }
+
+    /// Substitutes the values matched by pattern variables into a template, renaming the other
+    /// symbols it contains. Symbols within a quoted datum are left as they are. Returns `None` if
+    /// the template uses a pattern variable at the wrong depth of ellipses.
+    fn instantiate(
+        &mut self,
+        template: &Rc<Value>,
+        bindings: &Bindings,
+        ellipsis_enabled: bool,
+    ) -> Option<Rc<Value>> {
+        self.instantiate_inner(template, bindings, ellipsis_enabled, true)
+    }
+
+    fn instantiate_inner(
+        &mut self,
+        template: &Rc<Value>,
+        bindings: &Bindings,
+        ellipsis_enabled: bool,
+        rename: bool,
+    ) -> Option<Rc<Value>> {
+        if let Some(name) = template.as_symbol() {
+            return match bindings.get(name) {
+                Some(Binding::One(value)) => Some(value.clone()),
+                Some(Binding::Many(_)) => None,
+                None if rename => Some(self.rename(template, name)),
+                None => Some(template.clone()),
+            };
+        }
+        if !matches!(&**template, Value::Pair(_)) {
+            return Some(template.clone());
+        }
+        let (elements, end) = list::to_vec_with_end(template);
+        let head = &elements[0];
+        // `(... template)` escapes the ellipsis within the template
+        if let ([_, escaped], true) = (elements.as_slice(), ellipsis_enabled) {
+            if self.rules.is_ellipsis(head) && end.is_nil() {
+                return self.instantiate_inner(escaped, bindings, false, rename);
+            }
+        }
+        let is_quote = head.syntactic_name() == Some(Keyword::Quote.as_str())
+            && !head
+                .as_symbol()
+                .is_some_and(|name| bindings.contains_key(name));
+        let rename = rename && !is_quote;
+        let mut values = Vec::new();
+        let mut i = 0;
+        while i < elements.len() {
+            let element = &elements[i];
+            let mut depth = 0;
+            while ellipsis_enabled
+                && elements
+                    .get(i + depth + 1)
+                    .is_some_and(|next| self.rules.is_ellipsis(next))
+            {
+                depth += 1;
+            }
+            if depth == 0 {
+                values.push(self.instantiate_inner(element, bindings, ellipsis_enabled, rename)?);
+            } else {
+                self.instantiate_repeated(element, bindings, depth, rename, &mut values)?;
+            }
+            i += depth + 1;
+        }
+        let end = self.instantiate_inner(&end, bindings, ellipsis_enabled, rename)?;
+        Some(list::from_rc_vec_with_end(values, end))
+    }
+
+    /// Instantiates a template followed by `depth` ellipses once for each value matched by the
+    /// sequence variables it contains
+    fn instantiate_repeated(
+        &mut self,
+        template: &Rc<Value>,
+        bindings: &Bindings,
+        depth: usize,
+        rename: bool,
+        values: &mut Vec<Rc<Value>>,
+    ) -> Option<()> {
+        let mut variables = Vec::new();
+        template_symbols(template, &mut variables);
+        let sequences = variables
+            .iter()
+            .filter_map(|name| match bindings.get(name) {
+                Some(Binding::Many(sequence)) => Some((name.clone(), sequence)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let count = sequences.first()?.1.len();
+        if sequences
+            .iter()
+            .any(|(_, sequence)| sequence.len() != count)
+        {
+            return None;
+        }
+        for i in 0..count {
+            let mut bindings = bindings.clone();
+            for (name, sequence) in sequences.iter() {
+                bindings.insert(name.clone(), sequence[i].clone());
+            }
+            if depth == 1 {
+                values.push(self.instantiate_inner(template, &bindings, true, rename)?);
+            } else {
+                self.instantiate_repeated(template, &bindings, depth - 1, rename, values)?;
+            }
+        }
+        Some(())
+    }
+}
+
+/// Applies `f` to the elements of a possibly improper list and the value ending it, reusing the
+/// list if none of them change (so that values keep their locations in the source)
+fn map_list<F: FnMut(&Rc<Value>) -> Rc<Value>>(list: &Rc<Value>, mut f: F) -> Rc<Value> {
+    let (elements, end) = list::to_vec_with_end(list);
+    let mapped = elements.iter().map(&mut f).collect::<Vec<_>>();
+    let mapped_end = f(&end);
+    let unchanged = elements
+        .iter()
+        .chain([&end])
+        .zip(mapped.iter().chain([&mapped_end]))
+        .all(|(a, b)| Rc::ptr_eq(a, b));
+    if unchanged {
+        list.clone()
+    } else {
+        list::from_rc_vec_with_end(mapped, mapped_end)
+    }
+}
+
+/// Replaces each identifier in a datum with the symbol it was renamed from
+fn strip_identifiers(datum: &Rc<Value>) -> Rc<Value> {
+    match &**datum {
+        Value::Identifier(identifier) => strip_identifiers(&identifier.symbol),
+        Value::Pair(_) => map_list(datum, strip_identifiers),
+        _ => datum.clone(),
+    }
+}
+
+/// Strips the identifiers from the parts of an expansion which are data rather than code: quoted
+/// data, the parts of a quasiquote template which aren't unquoted, and the data of `case`
+/// clauses. Otherwise renamed symbols would be visible to the program as values which aren't
+/// equal to the symbols they were written as.
+fn strip_data(form: &Rc<Value>) -> Rc<Value> {
+    let Value::Pair(pair) = &**form else {
+        return form.clone();
+    };
+    match pair.car().syntactic_name().and_then(Keyword::from_str) {
+        Some(Keyword::Quote) => strip_identifiers(form),
+        Some(Keyword::Quasiquote) => strip_quasiquoted(form, 0),
+        Some(Keyword::Case) => {
+            let mut i = 0;
+            map_list(form, |element| {
+                i += 1;
+                // the keyword and key are followed by clauses starting with their data
+                if i <= 2 {
+                    strip_data(element)
+                } else {
+                    let mut j = 0;
+                    map_list(element, |clause_element| {
+                        j += 1;
+                        if j == 1 {
+                            strip_identifiers(clause_element)
+                        } else {
+                            strip_data(clause_element)
+                        }
+                    })
+                }
+            })
+        }
+        _ => map_list(form, strip_data),
+    }
+}
+
+/// Strips identifiers from a form within the given depth of quasiquote nesting, matching the way
+/// that quasiquote templates are evaluated. Forms at depth zero are code.
+fn strip_quasiquoted(form: &Rc<Value>, depth: usize) -> Rc<Value> {
+    let Value::Pair(pair) = &**form else {
+        return if depth == 0 {
+            form.clone()
+        } else {
+            strip_identifiers(form)
+        };
+    };
+    let (car, cdr) = (pair.car(), pair.cdr());
+    let arg_depth = match car.syntactic_name().and_then(Keyword::from_str) {
+        _ if list::length(&cdr) != Some(1) => None,
+        Some(Keyword::Quasiquote) => Some(depth + 1),
+        Some(Keyword::Unquote | Keyword::UnquoteSplicing) if depth > 0 => Some(depth - 1),
+        _ => None,
+    };
+    match arg_depth {
+        Some(arg_depth) => {
+            let mut head = true;
+            map_list(form, |element| {
+                if std::mem::take(&mut head) {
+                    strip_identifiers(element)
+                } else {
+                    strip_quasiquoted(element, arg_depth)
+                }
+            })
+        }
+        None if depth == 0 => strip_data(form),
+        None => {
+            let stripped_car = strip_quasiquoted(&car, depth);
+            let stripped_cdr = strip_quasiquoted(&cdr, depth);
+            if Rc::ptr_eq(&car, &stripped_car) && Rc::ptr_eq(&cdr, &stripped_cdr) {
+                form.clone()
+            } else {
+                Rc::new(Value::pair(stripped_car, stripped_cdr))
+            }
+        }
+    }
+}
+
+/// The names of the symbols in a template
+fn template_symbols(template: &Value, symbols: &mut Vec<String>) {
+    match template {
+        Value::Pair(pair) => {
+            template_symbols(&pair.car(), symbols);
+            template_symbols(&pair.cdr(), symbols);
+        }
+        _ => {
+            if let Some(name) = template.as_symbol() {
+                symbols.push(name.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SyntaxRules;
+    use crate::{eval::Scope, parse, value::Value};
+    use std::rc::Rc;
+
+    fn syntax_rules(string: &str) -> SyntaxRules {
+        let form = Rc::new(parse::parse(string).unwrap().remove(0));
+        let args = match &*form {
+            Value::Pair(pair) => pair.cdr(),
+            _ => panic!("expected a syntax-rules form"),
+        };
+        SyntaxRules::new(&form, &args, &Scope::new(None)).unwrap()
+    }
+
+    #[test]
+    fn renaming() {
+        let rules = syntax_rules("(syntax-rules () ((_ a) (let ((tmp a)) (f tmp 'tmp))))");
+        let form = Rc::new(parse::parse("(m tmp)").unwrap().remove(0));
+        let mut next_alias = 0;
+        let first = rules.expand(&form, &mut next_alias).unwrap();
+        let second = rules.expand(&form, &mut next_alias).unwrap();
+        assert_eq!(first.to_string(), "(let ((tmp tmp)) (f tmp (quote tmp)))");
+        // each expansion introduces its own identifiers, which are distinct from the symbol
+        // passed to the macro, and quoted symbols are left alone
+        assert_eq!(
+            format!("{:?}", first).matches("Identifier {").count(),
+            4,
+            "{:?}",
+            first
+        );
+        assert_ne!(first, second);
+        assert_eq!(next_alias, 6);
+    }
+}
@@ -1,22 +1,51 @@
-use crate::{built_in::BuiltIn, eval::Lambda};
-use std::rc::Rc;
+use crate::{
+    built_in::BuiltIn,
+    eval::Lambda,
+    syntax_rules::{Identifier, SyntaxRules},
+};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use std::{cell::RefCell, mem, rc::Rc};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub enum Atom {
     Nil,
     Symbol(String),
     String(String),
     I64(i64),
+    /// An integer outside the range of i64
+    BigInt(BigInt),
+    /// A fraction in lowest terms whose denominator is greater than one
+    Rational(BigRational),
+    F64(f64),
     Bool(bool),
+    Char(char),
 }
 
+// Floats are compared by their bits (as with `eqv?`), so NaN is equal to itself and 0.0 is
+// distinct from -0.0. Numeric comparison is implemented by `Number`.
+impl PartialEq for Atom {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Nil, Self::Nil) => true,
+            (Self::Symbol(a), Self::Symbol(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::I64(a), Self::I64(b)) => a == b,
+            (Self::BigInt(a), Self::BigInt(b)) => a == b,
+            (Self::Rational(a), Self::Rational(b)) => a == b,
+            (Self::F64(a), Self::F64(b)) => a.to_bits() == b.to_bits(),
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Char(a), Self::Char(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Atom {}
+
 impl Atom {
     pub fn is_nil(&self) -> bool {
-        if let Self::Nil = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Self::Nil)
     }
 
     pub fn symbol(&self) -> Option<&str> {
@@ -28,13 +57,63 @@ impl Atom {
     }
 }
 
+/// A cons cell. Both halves can be replaced in place by `set-car!` and `set-cdr!`.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Pair(pub Rc<Value>, pub Rc<Value>);
+pub struct Pair {
+    car: RefCell<Rc<Value>>,
+    cdr: RefCell<Rc<Value>>,
+}
+
+impl Pair {
+    pub fn new(car: Rc<Value>, cdr: Rc<Value>) -> Self {
+        Self {
+            car: RefCell::new(car),
+            cdr: RefCell::new(cdr),
+        }
+    }
+
+    pub fn car(&self) -> Rc<Value> {
+        self.car.borrow().clone()
+    }
+
+    pub fn cdr(&self) -> Rc<Value> {
+        self.cdr.borrow().clone()
+    }
+
+    pub fn set_car(&self, value: Rc<Value>) {
+        *self.car.borrow_mut() = value;
+    }
+
+    pub fn set_cdr(&self, value: Rc<Value>) {
+        *self.cdr.borrow_mut() = value;
+    }
+}
+
+impl Drop for Pair {
+    /// Unlinks the pairs of a list which are owned only by the list one at a time, as dropping
+    /// them recursively would overflow the stack for long lists
+    fn drop(&mut self) {
+        let cdr = self.cdr.get_mut();
+        if Rc::strong_count(cdr) > 1 || !matches!(**cdr, Value::Pair(_)) {
+            return;
+        }
+        let nil = Rc::new(Value::nil());
+        let mut rest = mem::replace(cdr, nil.clone());
+        while let Ok(Value::Pair(mut pair)) = Rc::try_unwrap(rest) {
+            rest = mem::replace(pair.cdr.get_mut(), nil.clone());
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Function {
     Lambda(Lambda),
     BuiltIn(BuiltIn),
+    /// Defined by `defmacro`. Called with the unevaluated arguments of a form, returning the
+    /// form to evaluate in its place.
+    Macro(Lambda),
+    /// Defined by `syntax-rules`
+    SyntaxRules(Rc<SyntaxRules>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -42,6 +121,8 @@ pub enum Value {
     Atom(Atom),
     Pair(Pair),
     Function(Function),
+    /// A symbol renamed by the expansion of a `syntax-rules` macro
+    Identifier(Identifier),
 }
 
 impl Value {
@@ -61,15 +142,96 @@ impl Value {
         Self::Atom(Atom::I64(i))
     }
 
+    pub const fn f64(f: f64) -> Self {
+        Self::Atom(Atom::F64(f))
+    }
+
     pub const fn bool(b: bool) -> Self {
         Self::Atom(Atom::Bool(b))
     }
 
+    pub const fn char(c: char) -> Self {
+        Self::Atom(Atom::Char(c))
+    }
+
     pub fn pair(a: Rc<Self>, b: Rc<Self>) -> Self {
-        Self::Pair(Pair(a, b))
+        Self::Pair(Pair::new(a, b))
     }
 
     pub fn built_in(built_in: BuiltIn) -> Self {
         Self::Function(Function::BuiltIn(built_in))
     }
+
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Self::Atom(Atom::Nil))
+    }
+
+    /// The name under which a symbol (or an identifier renamed by a macro expansion) is bound
+    pub fn as_symbol(&self) -> Option<&str> {
+        match self {
+            Self::Atom(atom) => atom.symbol(),
+            Self::Identifier(identifier) => Some(identifier.name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// The name of a symbol as it was written, ignoring any renaming by macro expansions. This is
+    /// used to recognise keywords.
+    pub fn syntactic_name(&self) -> Option<&str> {
+        match self {
+            Self::Atom(atom) => atom.symbol(),
+            Self::Identifier(identifier) => Some(identifier.syntactic_name()),
+            _ => None,
+        }
+    }
+
+    /// Every value other than `false` counts as true in a condition
+    pub fn is_true(&self) -> bool {
+        !matches!(self, Self::Atom(Atom::Bool(false)))
+    }
+
+    /// Identity: whether both are the same value in memory. Symbols, nil and booleans aren't
+    /// shared between the places they appear, so they are identical whenever they are equal.
+    pub fn is_eq(a: &Rc<Self>, b: &Rc<Self>) -> bool {
+        match (&**a, &**b) {
+            (
+                Self::Atom(a @ (Atom::Symbol(_) | Atom::Nil | Atom::Bool(_))),
+                Self::Atom(b @ (Atom::Symbol(_) | Atom::Nil | Atom::Bool(_))),
+            ) => a == b,
+            _ => Rc::ptr_eq(a, b),
+        }
+    }
+
+    /// Equivalence as used by `case`: atoms other than strings are compared by value, and
+    /// everything else by identity
+    pub fn is_eqv(a: &Rc<Self>, b: &Rc<Self>) -> bool {
+        match (&**a, &**b) {
+            (Self::Atom(Atom::String(_)), Self::Atom(Atom::String(_))) => Rc::ptr_eq(a, b),
+            (Self::Atom(a), Self::Atom(b)) => a == b,
+            (Self::Function(a), Self::Function(b)) => a == b,
+            _ => Rc::ptr_eq(a, b),
+        }
+    }
+
+    /// Structural equality: pairs are equal if their cars and cdrs are, strings if they have the
+    /// same contents, and everything else if they are equivalent
+    pub fn is_equal(a: &Rc<Self>, b: &Rc<Self>) -> bool {
+        // the cdrs of lists are compared in a loop, as recursing on them would overflow the
+        // stack for long lists
+        let (mut a, mut b) = (a.clone(), b.clone());
+        loop {
+            let (next_a, next_b) = match (&*a, &*b) {
+                (Self::Pair(pair_a), Self::Pair(pair_b)) => {
+                    if !Self::is_equal(&pair_a.car(), &pair_b.car()) {
+                        return false;
+                    }
+                    (pair_a.cdr(), pair_b.cdr())
+                }
+                (Self::Atom(Atom::String(a)), Self::Atom(Atom::String(b))) => return a == b,
+                _ => return Self::is_eqv(&a, &b),
+            };
+            a = next_a;
+            b = next_b;
+        }
+    }
 }